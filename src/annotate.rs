@@ -21,7 +21,21 @@ pub enum AnnotationError {
 const DEFAULT_KEY_FIELDS: [&str; 1] = ["ehars_uid"];
 const DEFAULT_KEY_DELIMITER: &str = "~";
 
+// Schema keys that configure annotation rather than describe attributes
+const RESERVED_SCHEMA_KEYS: [&str; 2] = ["keying", "filter"];
+
+// Fields identifying the endpoints of an edge in edge attribute records
+const EDGE_KEY_FIELDS: [&str; 2] = ["source_id", "target_id"];
+
+/// Check if a schema key configures annotation rather than describing an attribute
+fn is_reserved_key(key: &str) -> bool {
+    RESERVED_SCHEMA_KEYS.contains(&key)
+}
+
 /// Main function to annotate a network JSON with attribute data
+///
+/// Annotation is additive: attributes and schema entries from earlier passes
+/// are kept, and only the fields in `schema_json` are added or updated.
 pub fn annotate_network(
     network_json: &str,
     attributes_json: &str,
@@ -49,15 +63,33 @@ pub fn annotate_network(
     ensure_key(network_data, "patient_attribute_schema");
     
     // Create attribute schema from input schema
-    create_attribute_schema(network_data, &schema)?;
+    create_attribute_schema(network_data, "patient_attribute_schema", &schema)?;
     
     // Handle hivcluster_rs format - nodes as object with parallel arrays
     let mut node_key_map: HashMap<String, usize> = HashMap::new();
     let mut uninjected_fields: HashMap<String, HashSet<String>> = HashMap::new();
     
+    // Track the declared (or inferred) type of each attribute field
+    let mut field_types: HashMap<String, String> = HashMap::new();
+    let mut field_types_declared: HashSet<String> = HashSet::new();
+    for (field, field_info) in schema.iter() {
+        if is_reserved_key(field) {
+            continue;
+        }
+        match field_info.get("type").and_then(|t| t.as_str()) {
+            Some(field_type) => {
+                field_types.insert(field.clone(), field_type.to_string());
+                field_types_declared.insert(field.clone());
+            }
+            None => {
+                field_types.insert(field.clone(), "String".to_string());
+            }
+        }
+    }
+    
     // Initialize uninjected fields for tracking
     for (field, _) in schema.iter() {
-        if field != "keying" {
+        if !is_reserved_key(field) {
             uninjected_fields.insert(field.clone(), HashSet::new());
         }
     }
@@ -131,20 +163,28 @@ pub fn annotate_network(
             
             // Apply each attribute to the node
             for (field_name, field_value) in attributes.iter() {
-                if schema.contains_key(field_name) && field_name != "keying" {
+                if schema.contains_key(field_name) && !is_reserved_key(field_name) {
                     let nodes_obj = network_data["Nodes"].as_object_mut().unwrap();
                     
                     // Get the patient_attributes array
                     let patient_attrs_array = nodes_obj["patient_attributes"].as_array_mut().unwrap();
                     
                     // Add the attribute to the node's patient_attributes object
-                    // Ensure that null values are converted to empty strings
+                    // Ensure that null values are converted to the field's empty value;
+                    // arrays and objects are kept as structured values
                     let processed_value = if field_value.is_null() {
-                        json!("")
+                        empty_value_for_type(&field_types[field_name])
                     } else {
                         field_value.clone()
                     };
                     
+                    // Fields without a declared type take their type from structured values
+                    if !field_types_declared.contains(field_name) {
+                        if let Some(structured_type) = structured_value_type(field_value) {
+                            field_types.insert(field_name.clone(), structured_type.to_string());
+                        }
+                    }
+                    
                     patient_attrs_array[*node_idx][field_name] = processed_value;
                     
                     // Remove node from uninjected set for this field
@@ -156,17 +196,24 @@ pub fn annotate_network(
         }
     }
     
-    // Process uninjected fields - ensure any remaining null values are replaced with empty strings
+    // Record types inferred from structured values in the attribute schema
+    for (field_name, field_type) in field_types.iter() {
+        if !field_types_declared.contains(field_name) {
+            network_data["patient_attribute_schema"][field_name]["type"] = json!(field_type);
+        }
+    }
+    
+    // Process uninjected fields - ensure any remaining null values are replaced with empty values
     if let Some(nodes_obj) = network_data.get_mut("Nodes").and_then(|n| n.as_object_mut()) {
         if let Some(patient_attrs_array) = nodes_obj.get_mut("patient_attributes").and_then(|p| p.as_array_mut()) {
             for attr_obj in patient_attrs_array.iter_mut() {
                 if let Some(obj) = attr_obj.as_object_mut() {
                     // Ensure all schema fields exist in each patient_attributes object
                     for (field_name, _) in schema.iter() {
-                        if field_name != "keying" {
-                            // If field doesn't exist or is null, set it to empty string
+                        if !is_reserved_key(field_name) {
+                            // If field doesn't exist or is null, set it to the field's empty value
                             if !obj.contains_key(field_name) || obj[field_name].is_null() {
-                                obj.insert(field_name.clone(), json!(""));
+                                obj.insert(field_name.clone(), empty_value_for_type(&field_types[field_name]));
                             }
                         }
                     }
@@ -175,11 +222,349 @@ pub fn annotate_network(
         }
     }
     
+    // Drop nodes that don't match the optional attribute filter
+    if let Some(filter) = schema.get("filter") {
+        apply_node_filter(network_data, filter)?;
+    }
+    
+    // Report how many nodes ended up without a value for each field, including
+    // fields recorded by earlier annotation passes (annotation is additive)
+    let mut count_types = field_types.clone();
+    if let Some(existing_counts) = network_data["patient_attribute_missing_counts"].as_object() {
+        for field_name in existing_counts.keys() {
+            if !count_types.contains_key(field_name) {
+                let field_type = network_data["patient_attribute_schema"][field_name]["type"]
+                    .as_str()
+                    .unwrap_or("String");
+                count_types.insert(field_name.clone(), field_type.to_string());
+            }
+        }
+    }
+    let missing_counts = count_missing_values(network_data, &count_types);
+    network_data["patient_attribute_missing_counts"] = json!(missing_counts);
+    
+    // Convert to JSON string
+    let result = serde_json::to_string_pretty(&network)?;
+    Ok(result)
+}
+
+/// Annotate the edges of a network JSON with per-edge attribute data
+///
+/// Records are keyed by `source_id` and `target_id`, matched in either
+/// orientation. Values land in `Edges.attributes.edge_attributes`, one object
+/// per edge, and the schema is recorded under `edge_attribute_schema`.
+pub fn annotate_edges(
+    network_json: &str,
+    edge_attributes_json: &str,
+    schema_json: &str,
+) -> Result<String, AnnotationError> {
+    // Parse input JSON files
+    let mut network: Value = serde_json::from_str(network_json)?;
+    let attributes: Vec<HashMap<String, Value>> = parse_attributes(edge_attributes_json)?;
+    let schema: HashMap<String, Value> = serde_json::from_str(schema_json)?;
+    
+    // Get network data (either at root or under trace_results)
+    let network_data = if network.get("trace_results").is_some() {
+        network.get_mut("trace_results").unwrap()
+    } else {
+        &mut network
+    };
+    
+    ensure_key(network_data, "edge_attribute_schema");
+    create_attribute_schema(network_data, "edge_attribute_schema", &schema)?;
+    
+    let field_types: HashMap<String, String> = schema.iter()
+        .filter(|(field, _)| !is_reserved_key(field) && !EDGE_KEY_FIELDS.contains(&field.as_str()))
+        .map(|(field, field_info)| {
+            let field_type = field_info.get("type").and_then(|t| t.as_str()).unwrap_or("String");
+            (field.clone(), field_type.to_string())
+        })
+        .collect();
+    
+    // Resolve each edge to the (source_id, target_id) pair of its endpoints
+    let ids: Vec<String> = network_data["Nodes"]["id"].as_array()
+        .ok_or_else(|| AnnotationError::MissingField("Nodes.id array".to_string()))?
+        .iter()
+        .map(value_to_string)
+        .collect();
+    let edge_index = |key: &str| -> Result<Vec<usize>, AnnotationError> {
+        network_data["Edges"][key].as_array()
+            .ok_or_else(|| AnnotationError::MissingField(format!("Edges.{} array", key)))?
+            .iter()
+            .map(|v| {
+                v.as_u64()
+                    .map(|i| i as usize)
+                    .filter(|i| *i < ids.len())
+                    .ok_or_else(|| AnnotationError::InvalidFormat(format!("Invalid node index in Edges.{}", key)))
+            })
+            .collect()
+    };
+    let sources = edge_index("source")?;
+    let targets = edge_index("target")?;
+    
+    // Index the attribute records by their unordered endpoint pair
+    let mut attribute_map: HashMap<(String, String), &HashMap<String, Value>> = HashMap::new();
+    for record in attributes.iter() {
+        let source = record.get(EDGE_KEY_FIELDS[0]).map(value_to_string);
+        let target = record.get(EDGE_KEY_FIELDS[1]).map(value_to_string);
+        if let (Some(source), Some(target)) = (source, target) {
+            attribute_map.insert(edge_key(source, target), record);
+        }
+    }
+    
+    // Build one attribute object per edge, filling gaps with empty values
+    let edge_attributes: Vec<Value> = sources.iter().zip(targets.iter())
+        .map(|(&source, &target)| {
+            let record = attribute_map.get(&edge_key(ids[source].clone(), ids[target].clone()));
+            let mut obj = serde_json::Map::new();
+            for (field_name, field_type) in field_types.iter() {
+                let value = record
+                    .and_then(|r| r.get(field_name))
+                    .filter(|v| !v.is_null())
+                    .cloned()
+                    .unwrap_or_else(|| empty_value_for_type(field_type));
+                obj.insert(field_name.clone(), value);
+            }
+            Value::Object(obj)
+        })
+        .collect();
+    
+    ensure_key(&mut network_data["Edges"], "attributes");
+    network_data["Edges"]["attributes"]["edge_attributes"] = json!(edge_attributes);
+    
     // Convert to JSON string
     let result = serde_json::to_string_pretty(&network)?;
     Ok(result)
 }
 
+/// Key an edge by its endpoint IDs regardless of orientation
+fn edge_key(source: String, target: String) -> (String, String) {
+    if source <= target {
+        (source, target)
+    } else {
+        (target, source)
+    }
+}
+
+/// Count nodes whose value for each schema field is empty after annotation
+fn count_missing_values(network_data: &Value, field_types: &HashMap<String, String>) -> HashMap<String, usize> {
+    let patient_attrs = network_data["Nodes"]["patient_attributes"].as_array();
+    
+    field_types.iter()
+        .map(|(field_name, field_type)| {
+            let empty_value = empty_value_for_type(field_type);
+            let missing = patient_attrs
+                .map(|attrs| {
+                    attrs.iter()
+                        .filter(|attr_obj| {
+                            let value = &attr_obj[field_name];
+                            value.is_null() || *value == empty_value || *value == json!("")
+                        })
+                        .count()
+                })
+                .unwrap_or(0);
+            (field_name.clone(), missing)
+        })
+        .collect()
+}
+
+/// Summary of how well attribute records match an annotation schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotationReport {
+    /// Number of attribute records
+    pub record_count: usize,
+    /// Schema fields present in at least one record (sorted)
+    pub covered_fields: Vec<String>,
+    /// Schema fields absent from every record (sorted)
+    pub missing_fields: Vec<String>,
+    /// Number of records missing one or more key fields
+    pub missing_key_records: usize,
+}
+
+/// Check attribute records against a schema before running an annotation
+///
+/// Reports which schema fields (other than configuration entries like
+/// `keying`) never appear in the records, and how many records lack the key
+/// fields needed to match them to nodes.
+pub fn validate_annotation_inputs(
+    attributes_json: &str,
+    schema_json: &str,
+) -> Result<AnnotationReport, AnnotationError> {
+    let attributes: Vec<HashMap<String, Value>> = parse_attributes(attributes_json)?;
+    let schema: HashMap<String, Value> = serde_json::from_str(schema_json)?;
+    let (key_fields, _) = extract_key_info(&schema);
+    
+    let mut covered_fields = Vec::new();
+    let mut missing_fields = Vec::new();
+    for field_name in schema.keys().filter(|key| !is_reserved_key(key)) {
+        if attributes.iter().any(|record| record.contains_key(field_name)) {
+            covered_fields.push(field_name.clone());
+        } else {
+            missing_fields.push(field_name.clone());
+        }
+    }
+    covered_fields.sort();
+    missing_fields.sort();
+    
+    let missing_key_records = attributes.iter()
+        .filter(|record| key_fields.iter().any(|field| !record.contains_key(field)))
+        .count();
+    
+    Ok(AnnotationReport {
+        record_count: attributes.len(),
+        covered_fields,
+        missing_fields,
+        missing_key_records,
+    })
+}
+
+/// Empty placeholder for a missing attribute value of the given schema type
+fn empty_value_for_type(field_type: &str) -> Value {
+    match field_type {
+        "Array" => json!([]),
+        "Object" => json!({}),
+        _ => json!(""),
+    }
+}
+
+/// Schema type for a structured (array or object) attribute value
+fn structured_value_type(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::Array(_) => Some("Array"),
+        Value::Object(_) => Some("Object"),
+        _ => None,
+    }
+}
+
+/// Remove nodes whose attribute value isn't in the allowed set, remapping edges
+///
+/// The filter is given in the schema as `{"field": "country", "values": ["US"]}`.
+/// All parallel arrays in `Nodes` and `Edges` are pruned and edge endpoints are
+/// re-indexed; edges touching a removed node are dropped.
+fn apply_node_filter(network_data: &mut Value, filter: &Value) -> Result<(), AnnotationError> {
+    let field = filter.get("field")
+        .and_then(|f| f.as_str())
+        .ok_or_else(|| AnnotationError::InvalidFormat("filter.field must be a string".to_string()))?;
+    
+    let allowed: HashSet<String> = filter.get("values")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| AnnotationError::InvalidFormat("filter.values must be an array".to_string()))?
+        .iter()
+        .map(value_to_string)
+        .collect();
+    
+    // Decide which nodes to keep
+    let keep_nodes: Vec<bool> = network_data["Nodes"]["patient_attributes"].as_array()
+        .ok_or_else(|| AnnotationError::MissingField("Nodes.patient_attributes array".to_string()))?
+        .iter()
+        .map(|attrs| attrs.get(field).map(|v| allowed.contains(&value_to_string(v))).unwrap_or(false))
+        .collect();
+    
+    // Map old node indices to new ones
+    let new_node_index = pruned_indices(&keep_nodes);
+    
+    // Prune the Nodes parallel arrays
+    if let Some(nodes_obj) = network_data["Nodes"].as_object_mut() {
+        for (_, value) in nodes_obj.iter_mut() {
+            prune_parallel_array(value, &keep_nodes);
+        }
+    }
+    
+    // Decide which edges to keep, remapping their endpoints
+    let edges_obj = match network_data.get_mut("Edges").and_then(|e| e.as_object_mut()) {
+        Some(edges_obj) => edges_obj,
+        None => return Ok(()),
+    };
+    
+    let endpoint_indices = |edges_obj: &serde_json::Map<String, Value>, key: &str| -> Vec<Option<usize>> {
+        edges_obj.get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().map(|v| v.as_u64().map(|i| i as usize)).collect())
+            .unwrap_or_default()
+    };
+    let sources = endpoint_indices(edges_obj, "source");
+    let targets = endpoint_indices(edges_obj, "target");
+    
+    let remap = |idx: Option<usize>| idx.and_then(|i| new_node_index.get(i).copied().flatten());
+    let keep_edges: Vec<bool> = sources.iter().zip(targets.iter())
+        .map(|(&s, &t)| remap(s).is_some() && remap(t).is_some())
+        .collect();
+    
+    for (key, value) in edges_obj.iter_mut() {
+        prune_parallel_array(value, &keep_edges);
+        
+        // Nested sections (directed, removed, ...) hold their own parallel arrays
+        if let Some(section) = value.as_object_mut() {
+            if let Some(values) = section.get_mut("values") {
+                prune_parallel_array(values, &keep_edges);
+            }
+            if let Some(reasons) = section.get_mut("reasons") {
+                remap_index_keys(reasons, &keep_edges);
+            }
+        }
+        
+        // Re-index endpoints to the pruned node arrays
+        if key == "source" || key == "target" {
+            if let Some(indices) = value.as_array_mut() {
+                for index in indices.iter_mut() {
+                    if let Some(new_idx) = remap(index.as_u64().map(|i| i as usize)) {
+                        *index = json!(new_idx);
+                    }
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Map each old array index to its index after pruning (None if removed)
+fn pruned_indices(keep: &[bool]) -> Vec<Option<usize>> {
+    let mut next_index = 0;
+    keep.iter()
+        .map(|&k| {
+            if k {
+                next_index += 1;
+                Some(next_index - 1)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Keep only the entries of a parallel array whose flag is set
+fn prune_parallel_array(value: &mut Value, keep: &[bool]) {
+    if let Some(array) = value.as_array_mut() {
+        if array.len() == keep.len() {
+            let mut flags = keep.iter();
+            array.retain(|_| *flags.next().unwrap_or(&false));
+        }
+    }
+}
+
+/// Re-key an object keyed by array index after pruning that array
+fn remap_index_keys(value: &mut Value, keep: &[bool]) {
+    if let Some(obj) = value.as_object_mut() {
+        let new_index = pruned_indices(keep);
+        
+        let old = std::mem::take(obj);
+        for (key, entry) in old {
+            if let Some(Some(idx)) = key.parse::<usize>().ok().and_then(|i| new_index.get(i).copied()) {
+                obj.insert(idx.to_string(), entry);
+            }
+        }
+    }
+}
+
+/// Render an attribute value as a string for comparison
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
 /// Parse attributes from JSON string, handling both array and object formats
 fn parse_attributes(json_str: &str) -> Result<Vec<HashMap<String, Value>>, AnnotationError> {
     // Try parsing as an array first
@@ -198,6 +583,33 @@ fn parse_attributes(json_str: &str) -> Result<Vec<HashMap<String, Value>>, Annot
     Err(AnnotationError::InvalidFormat("Attributes JSON must be an array or object".to_string()))
 }
 
+/// Convert CSV attributes (with a header row) to the JSON array of records
+/// accepted by `annotate_network`
+///
+/// Each row becomes one record with string values keyed by the header
+/// names; empty cells are left out, as they would be in JSON.
+pub fn attributes_csv_to_json(csv_str: &str) -> Result<String, AnnotationError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(csv_str.as_bytes());
+    let headers = reader.headers()
+        .map_err(|e| AnnotationError::InvalidFormat(e.to_string()))?
+        .clone();
+    
+    let mut records = Vec::new();
+    for result in reader.records() {
+        let row = result.map_err(|e| AnnotationError::InvalidFormat(e.to_string()))?;
+        let record: serde_json::Map<String, Value> = headers.iter()
+            .zip(row.iter())
+            .filter(|(_, value)| !value.trim().is_empty())
+            .map(|(key, value)| (key.trim().to_string(), json!(value.trim())))
+            .collect();
+        records.push(Value::Object(record));
+    }
+    
+    Ok(serde_json::to_string(&records)?)
+}
+
 /// Extract key fields and delimiter from schema, or use defaults
 fn extract_key_info(schema: &HashMap<String, Value>) -> (Vec<String>, String) {
     let mut key_fields = DEFAULT_KEY_FIELDS.iter().map(|s| s.to_string()).collect::<Vec<_>>();
@@ -231,15 +643,19 @@ fn ensure_key<'a>(obj: &'a mut Value, key: &str) -> &'a mut Value {
     obj.get_mut(key).unwrap()
 }
 
-/// Create the attribute schema in the network data
-fn create_attribute_schema(network_data: &mut Value, schema: &HashMap<String, Value>) -> Result<(), AnnotationError> {
+/// Create the attribute schema in the network data under `schema_key`
+fn create_attribute_schema(
+    network_data: &mut Value,
+    schema_key: &str,
+    schema: &HashMap<String, Value>,
+) -> Result<(), AnnotationError> {
     for (field_name, field_info) in schema.iter() {
-        // Skip the "keying" field as it's not part of the actual schema
-        if field_name == "keying" {
+        // Skip the "keying" and "filter" fields as they're not part of the actual schema
+        if is_reserved_key(field_name) {
             continue;
         }
         
-        let schema_entry = network_data["patient_attribute_schema"].as_object_mut().unwrap();
+        let schema_entry = network_data[schema_key].as_object_mut().unwrap();
         
         // Get field type and label
         let field_type = field_info.get("type")
@@ -264,9 +680,15 @@ fn create_attribute_schema(network_data: &mut Value, schema: &HashMap<String, Va
         if field_type == "enum" {
             if let Some(enum_values) = field_info.get("enum") {
                 if let Some(enum_array) = enum_values.as_array() {
-                    schema_entry.get_mut(field_name).unwrap()
-                        .as_object_mut().unwrap()
-                        .insert("enum".to_string(), json!(enum_array));
+                    let (values, colors) = split_enum_colors(enum_array);
+                    let entry = schema_entry.get_mut(field_name).unwrap()
+                        .as_object_mut().unwrap();
+                    entry.insert("enum".to_string(), json!(values));
+                    
+                    // Keep any per-value colors as a value -> color mapping
+                    if !colors.is_empty() {
+                        entry.insert("enum_colors".to_string(), Value::Object(colors));
+                    }
                 }
             }
         }
@@ -275,6 +697,29 @@ fn create_attribute_schema(network_data: &mut Value, schema: &HashMap<String, Va
     Ok(())
 }
 
+/// Split enum entries into plain values and a value -> color mapping
+///
+/// Entries are either plain values or `{value, color}` objects; the color is
+/// optional.
+fn split_enum_colors(enum_array: &[Value]) -> (Vec<Value>, serde_json::Map<String, Value>) {
+    let mut values = Vec::new();
+    let mut colors = serde_json::Map::new();
+    
+    for item in enum_array {
+        match item.get("value") {
+            Some(value) => {
+                if let Some(color) = item.get("color").and_then(|c| c.as_str()) {
+                    colors.insert(value_to_string(value), json!(color));
+                }
+                values.push(value.clone());
+            }
+            None => values.push(item.clone()),
+        }
+    }
+    
+    (values, colors)
+}
+
 /// Construct a key from a node ID and key fields
 fn construct_node_key(node_id: &str, key_fields: &[String], delimiter: &str) -> Result<String, AnnotationError> {
     // If we need to extract parts from the node ID
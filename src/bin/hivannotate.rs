@@ -1,4 +1,4 @@
-use hivcluster_rs::AnnotationError;
+use hivcluster_rs::{attributes_csv_to_json, version_string, write_atomic};
 use std::env;
 use std::fs;
 use std::process;
@@ -6,6 +6,10 @@ use std::process;
 fn main() {
     // Parse command line arguments
     let args: Vec<String> = env::args().collect();
+    if args.iter().skip(1).any(|arg| arg == "--version") {
+        println!("{}", version_string("hivannotate"));
+        return;
+    }
     let config = match parse_args(&args) {
         Ok(config) => config,
         Err(e) => {
@@ -32,6 +36,19 @@ fn main() {
         }
     };
 
+    // Spreadsheet exports (.csv) are converted to JSON attribute records
+    let attributes_json = if config.attributes_file.to_lowercase().ends_with(".csv") {
+        match attributes_csv_to_json(&attributes_json) {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("Error reading attributes CSV: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        attributes_json
+    };
+
     let schema_json = match fs::read_to_string(&config.schema_file) {
         Ok(data) => data,
         Err(e) => {
@@ -52,7 +69,7 @@ fn main() {
     // Write output
     match &config.output_file {
         Some(file) => {
-            match fs::write(file, &result) {
+            match write_atomic(file, result.as_bytes()) {
                 Ok(_) => {
                     println!("Annotated network saved to '{}'", file);
                 }
@@ -147,9 +164,10 @@ fn print_usage(program_name: &str) {
     eprintln!("Usage: {} [options]", program_name);
     eprintln!("Options:");
     eprintln!("  -n, --network <file>      Input network JSON file (required)");
-    eprintln!("  -a, --attributes <file>   Patient attributes JSON file (required)");
+    eprintln!("  -a, --attributes <file>   Patient attributes JSON file, or CSV with a header if .csv (required)");
     eprintln!("  -s, --schema <file>       Attribute schema JSON file (required)");
     eprintln!("  -o, --output <file>       Output JSON file (default: stdout)");
+    eprintln!("  --version                 Print the version and enabled features");
     eprintln!("");
     eprintln!("Example:");
     eprintln!("  {} -n network.json -a attributes.json -s schema.json -o annotated_network.json", program_name);
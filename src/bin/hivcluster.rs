@@ -1,12 +1,20 @@
-use hivcluster_rs::{InputFormat, NetworkError, TransmissionNetwork};
+use hivcluster_rs::{
+    sha256_hex, version_string, write_atomic, write_atomic_with, ColumnMapping, InputFormat,
+    NetworkError, TransmissionNetwork,
+};
 use std::env;
 use std::fs;
-use std::io::{self, Read};
+use std::io::{self, BufWriter, IsTerminal, Read, Write};
+use std::path::Path;
 use std::process;
 
 fn main() {
     // Parse command line arguments
     let args: Vec<String> = env::args().collect();
+    if args.iter().skip(1).any(|arg| arg == "--version") {
+        println!("{}", version_string("hivcluster"));
+        return;
+    }
     let config = match parse_args(&args) {
         Ok(config) => config,
         Err(e) => {
@@ -16,6 +24,16 @@ fn main() {
         }
     };
 
+    // Don't send compressed output to a terminal
+    if config.gzip
+        && config.output_file.is_none()
+        && config.split_clusters.is_none()
+        && io::stdout().is_terminal()
+    {
+        eprintln!("Error: --gzip needs -o <file> or redirected output when writing to a terminal");
+        process::exit(1);
+    }
+
     // Read input data
     let input_data = match read_input(&config.input_file) {
         Ok(data) => data,
@@ -27,6 +45,27 @@ fn main() {
 
     // Create network
     let mut network = TransmissionNetwork::new();
+    configure_output(&mut network, &config);
+    if config.percent_distances {
+        network.distance_scale = 0.01;
+    }
+    network.column_mapping = config.column_mapping.clone();
+    if config.include_provenance {
+        network.include_provenance = true;
+        network.input_file = config.input_file.clone();
+        network.input_sha256 = Some(sha256_hex(input_data.as_bytes()));
+    }
+
+    // Load node attributes from a sidecar CSV, applied as nodes are created
+    if let Some(file) = &config.node_metadata {
+        let result = fs::read_to_string(file)
+            .map_err(NetworkError::Io)
+            .and_then(|metadata| network.read_node_metadata_csv(&metadata));
+        if let Err(e) = result {
+            eprintln!("Error reading node metadata '{}': {}", file, e);
+            process::exit(1);
+        }
+    }
 
     // Parse input data and construct network
     match network.read_from_csv_str(&input_data, config.threshold, config.input_format) {
@@ -41,19 +80,65 @@ fn main() {
     network.compute_adjacency();
     network.compute_clusters();
 
-    // Generate JSON output
-    let json_str = match network.to_json_string_pretty() {
-        Ok(json) => json,
-        Err(e) => {
-            eprintln!("Error generating JSON: {}", e);
+    // Optionally write the input rows back out with their cluster IDs
+    if let Some(file) = &config.annotated_csv {
+        let result = network
+            .annotate_csv_with_clusters(&input_data, config.input_format)
+            .and_then(|csv| write_atomic(file, csv.as_bytes()).map_err(NetworkError::Io));
+        if let Err(e) = result {
+            eprintln!("Error writing annotated CSV '{}': {}", file, e);
             process::exit(1);
         }
-    };
+    }
 
-    // Write output
+    // Export the raw adjacency instead of the network JSON
+    if config.export_adjacency {
+        let result = network
+            .to_adjacency_json()
+            .and_then(|json| match &config.output_file {
+                Some(file) => {
+                    write_atomic(file, format!("{}\n", json).as_bytes()).map_err(NetworkError::Io)
+                }
+                None => {
+                    println!("{}", json);
+                    Ok(())
+                }
+            });
+        if let Err(e) = result {
+            eprintln!("Error exporting adjacency: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // Optionally write one file per cluster; the combined output is then only
+    // written when an output file is given
+    if let Some(dir) = &config.split_clusters {
+        match write_cluster_files(&network, dir, &config) {
+            Ok(count) => println!("Wrote {} cluster files to '{}'", count, dir),
+            Err(e) => {
+                eprintln!("Error writing cluster files to '{}': {}", dir, e);
+                process::exit(1);
+            }
+        }
+        if config.output_file.is_none() {
+            return;
+        }
+    }
+
+    // Write output, streaming the JSON rather than building it in memory
     match &config.output_file {
         Some(file) => {
-            match fs::write(file, &json_str) {
+            let result = write_atomic_with(file, |handle| {
+                let mut writer = BufWriter::new(handle);
+                if config.gzip {
+                    writer = network.write_json_gzip(writer)?;
+                } else {
+                    network.write_json_pretty(&mut writer)?;
+                }
+                writer.flush().map_err(NetworkError::Io)
+            });
+            match result {
                 Ok(_) => {
                     println!("Network saved to '{}'", file);
 
@@ -87,9 +172,77 @@ fn main() {
         }
         None => {
             // Print to stdout
-            println!("{}", json_str);
+            let mut writer = BufWriter::new(io::stdout().lock());
+            let result = if config.gzip {
+                network
+                    .write_json_gzip(writer)
+                    .and_then(|mut writer| writer.flush().map_err(NetworkError::Io))
+            } else {
+                network
+                    .write_json_pretty(&mut writer)
+                    .and_then(|_| writeln!(writer).map_err(NetworkError::Io))
+                    .and_then(|_| writer.flush().map_err(NetworkError::Io))
+            };
+            if let Err(e) = result {
+                eprintln!("Error generating JSON: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Apply the output options from the command line to a network
+fn configure_output(network: &mut TransmissionNetwork, config: &Config) {
+    network.zero_index_clusters = config.zero_index_clusters;
+    network.preserve_node_order = config.preserve_node_order;
+    network.nodes_as_objects_output = config.nodes_as_objects;
+}
+
+/// Write each cluster to `<dir>/cluster_<id>.json` and all singletons to
+/// `<dir>/singletons.json`, returning the number of files written
+fn write_cluster_files(
+    network: &TransmissionNetwork,
+    dir: &str,
+    config: &Config,
+) -> Result<usize, NetworkError> {
+    fs::create_dir_all(dir).map_err(NetworkError::Io)?;
+
+    let write_part = |part: &mut TransmissionNetwork, name: String| {
+        configure_output(part, config);
+        write_atomic_with(Path::new(dir).join(name), |handle| {
+            let mut writer = BufWriter::new(handle);
+            part.write_json_pretty(&mut writer)?;
+            writer.flush().map_err(NetworkError::Io)
+        })
+    };
+
+    let mut count = 0;
+    for detail in network.clusters_detailed() {
+        // Name files by the cluster ID reported in the combined output
+        let id = if config.zero_index_clusters {
+            detail.id
+        } else {
+            detail.id + 1
+        };
+        let mut cluster = network.extract_cluster(detail.id);
+        write_part(&mut cluster, format!("cluster_{}.json", id))?;
+        count += 1;
+    }
+
+    let singletons = network.extract_singleton_nodes();
+    if !singletons.is_empty() {
+        // Keep the singletons' cluster IDs from the combined output
+        let mut part = network.subnetwork(&singletons);
+        for (id, node) in part.nodes.iter_mut() {
+            node.cluster_id = network
+                .get_node(id)
+                .and_then(|original| original.cluster_id);
         }
+        write_part(&mut part, "singletons.json".to_string())?;
+        count += 1;
     }
+
+    Ok(count)
 }
 
 /// Configuration for the program
@@ -98,6 +251,17 @@ struct Config {
     output_file: Option<String>,
     threshold: f64,
     input_format: InputFormat,
+    zero_index_clusters: bool,
+    preserve_node_order: bool,
+    percent_distances: bool,
+    include_provenance: bool,
+    nodes_as_objects: bool,
+    gzip: bool,
+    split_clusters: Option<String>,
+    annotated_csv: Option<String>,
+    node_metadata: Option<String>,
+    column_mapping: Option<ColumnMapping>,
+    export_adjacency: bool,
 }
 
 /// Parse command line arguments
@@ -111,6 +275,17 @@ fn parse_args(args: &[String]) -> Result<Config, String> {
         output_file: None,
         threshold: 0.015, // Default threshold
         input_format: InputFormat::Plain,
+        zero_index_clusters: false,
+        preserve_node_order: false,
+        percent_distances: false,
+        include_provenance: false,
+        nodes_as_objects: false,
+        gzip: false,
+        split_clusters: None,
+        annotated_csv: None,
+        node_metadata: None,
+        column_mapping: None,
+        export_adjacency: false,
     };
 
     let mut i = 1;
@@ -145,14 +320,82 @@ fn parse_args(args: &[String]) -> Result<Config, String> {
                     return Err("Missing format".to_string());
                 }
 
-                config.input_format = match args[i].to_lowercase().as_str() {
-                    "aeh" => InputFormat::AEH,
-                    "lanl" => InputFormat::LANL,
-                    "plain" => InputFormat::Plain,
-                    "regex" => InputFormat::Regex,
-                    _ => return Err(format!("Unknown format: {}", args[i])),
+                config.input_format = args[i]
+                    .parse()
+                    .map_err(|_| format!("Unknown format: {}", args[i]))?;
+            }
+            "--zero-index-clusters" => {
+                config.zero_index_clusters = true;
+            }
+            "--preserve-order" => {
+                config.preserve_node_order = true;
+            }
+            "--percent" => {
+                config.percent_distances = true;
+            }
+            "--provenance" => {
+                config.include_provenance = true;
+            }
+            "--nodes-as-objects" => {
+                config.nodes_as_objects = true;
+            }
+            "--gzip" => {
+                config.gzip = true;
+            }
+            "--columns" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Missing column names".to_string());
+                }
+
+                let names: Vec<String> = args[i].split(',').map(|n| n.trim().to_string()).collect();
+                config.column_mapping = match names.as_slice() {
+                    [source, target, distance] | [source, target, distance, _] => {
+                        Some(ColumnMapping {
+                            source: source.clone(),
+                            target: target.clone(),
+                            distance: distance.clone(),
+                            support: names.get(3).cloned(),
+                        })
+                    }
+                    _ => {
+                        return Err(
+                            "Columns must be given as source,target,distance[,support]".to_string()
+                        )
+                    }
                 };
             }
+            "--node-metadata" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Missing node metadata file".to_string());
+                }
+                config.node_metadata = Some(args[i].clone());
+            }
+            "--annotate-csv" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Missing annotated CSV file".to_string());
+                }
+                config.annotated_csv = Some(args[i].clone());
+            }
+            "--export" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Missing export mode".to_string());
+                }
+                match args[i].to_lowercase().as_str() {
+                    "adjacency" => config.export_adjacency = true,
+                    _ => return Err(format!("Unknown export mode: {}", args[i])),
+                }
+            }
+            "--split-clusters" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Missing split clusters directory".to_string());
+                }
+                config.split_clusters = Some(args[i].clone());
+            }
             // Check if this is a non-option argument (input file)
             _ if !args[i].starts_with('-') => {
                 if config.input_file.is_none() {
@@ -168,6 +411,20 @@ fn parse_args(args: &[String]) -> Result<Config, String> {
         i += 1;
     }
 
+    // Exports are written as plain JSON
+    if config.export_adjacency && config.gzip {
+        return Err("--gzip cannot be combined with --export".to_string());
+    }
+
+    // Compressed output files always carry the .gz extension
+    if config.gzip {
+        if let Some(file) = config.output_file.as_mut() {
+            if !file.ends_with(".gz") {
+                file.push_str(".gz");
+            }
+        }
+    }
+
     Ok(config)
 }
 
@@ -192,10 +449,39 @@ fn print_usage(program_name: &str) {
     eprintln!("Options:");
     eprintln!("  -t, --threshold <value>  Distance threshold (default: 0.015)");
     eprintln!("  -o, --output <file>      Output JSON file (default: stdout)");
-    eprintln!("  -f, --format <format>    Input format: aeh, lanl, plain, regex (default: plain)");
+    eprintln!(
+        "  -f, --format <format>    Input format: aeh, lanl, plain, plain-date, regex (default: plain)"
+    );
+    eprintln!("  --version                Print the version and enabled features");
+    eprintln!("  --zero-index-clusters    Emit 0-based cluster IDs (singletons as -1)");
+    eprintln!("  --preserve-order         Emit nodes in first-seen input order instead of sorted");
+    eprintln!("  --percent                Input distances are percentages (1.5 means 0.015)");
+    eprintln!(
+        "  --provenance             Add a provenance block (version, input SHA-256, settings)"
+    );
+    eprintln!(
+        "  --columns <s,t,d[,sup]>  Header names of the source, target, distance, support columns"
+    );
+    eprintln!("  --node-metadata <file>   CSV of id,attr1,... merged into node attributes");
+    eprintln!(
+        "  --annotate-csv <file>    Write the input rows with source_cluster,target_cluster appended"
+    );
+    eprintln!(
+        "  --export adjacency       Write the adjacency JSON (node ID to neighbor IDs) instead"
+    );
+    eprintln!(
+        "  --split-clusters <dir>   Write cluster_<id>.json per cluster and singletons.json to <dir>"
+    );
+    eprintln!(
+        "  --gzip                   Gzip-compress the output (appends .gz to the output file)"
+    );
+    eprintln!(
+        "  --nodes-as-objects       Emit Nodes as an array of objects instead of parallel arrays"
+    );
     eprintln!("");
     eprintln!("Input formats:");
     eprintln!("  plain: Simple node IDs with no metadata");
+    eprintln!("  plain-date: Plain IDs with an embedded YYYY-MM-DD date extracted");
     eprintln!("  aeh:   Format 'ID | date | other_fields'");
     eprintln!("  lanl:  Format 'subtype_country_id_year'");
     eprintln!("  regex: Extract dates from IDs using regex patterns");
@@ -5,9 +5,22 @@ mod utils;
 mod annotate;
 
 // Re-export main types and functions
-pub use network::TransmissionNetwork;
-pub use types::{Edge, InputFormat, NetworkError, ParsedPatient, Patient};
-pub use annotate::{annotate_network, AnnotationError};
+pub use network::{
+    ClusterDetail, ClusterSizeSummary, ClusterStatistics, Provenance, TransmissionNetwork,
+};
+pub use types::{
+    AehFieldSchema, AttributeConflictPolicy, ClusterNaming, ColumnMapping, CsvLimits, DateBucket,
+    Edge, FilterReason, IdNormalization, IdOrdering, InputFormat, NetworkError, ParseWarning,
+    ParsedPatient, Patient, SequenceCountMode, ThresholdMode,
+};
+pub use annotate::{
+    annotate_edges, annotate_network, attributes_csv_to_json, validate_annotation_inputs,
+    AnnotationError, AnnotationReport,
+};
+pub use utils::{
+    describe_vector, enabled_features, sha256_hex, version_string, write_atomic,
+    write_atomic_with, VectorDescription,
+};
 
 #[cfg(target_arch = "wasm32")]
 mod wasm {
@@ -20,15 +33,15 @@ mod wasm {
         utils::setup_logging();
     }
 
+    /// Parse an input format name, treating unrecognised names as plain IDs
+    fn parse_format(format: &str) -> InputFormat {
+        format.parse().unwrap_or(InputFormat::Plain)
+    }
+
     /// WASM bindings for the network builder
     #[wasm_bindgen]
     pub fn build_network(csv_data: &str, threshold: f64, format: &str) -> Result<String, JsValue> {
-        let input_format = match format.to_lowercase().as_str() {
-            "aeh" => InputFormat::AEH,
-            "lanl" => InputFormat::LANL,
-            "regex" => InputFormat::Regex,
-            _ => InputFormat::Plain,
-        };
+        let input_format = parse_format(format);
 
         // Build the network
         let result = build_network_internal(csv_data, threshold, input_format)
@@ -37,6 +50,30 @@ mod wasm {
         Ok(result)
     }
 
+    /// WASM bindings for the network builder in lenient mode, returning
+    /// `{json, warnings}` so data-quality issues can be shown to the user
+    #[wasm_bindgen]
+    pub fn build_network_with_warnings(
+        csv_data: &str,
+        threshold: f64,
+        format: &str,
+    ) -> Result<String, JsValue> {
+        let input_format = parse_format(format);
+
+        build_network_with_warnings_internal(csv_data, threshold, input_format)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// WASM bindings for building the network from edge-tuple JSON
+    #[wasm_bindgen]
+    pub fn build_network_from_edge_tuples(
+        edges_json: &str,
+        threshold: f64,
+    ) -> Result<String, JsValue> {
+        build_network_from_edge_tuples_internal(edges_json, threshold)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Get network statistics in JSON format
     #[wasm_bindgen]
     pub fn get_network_stats(
@@ -44,30 +81,23 @@ mod wasm {
         threshold: f64,
         format: &str,
     ) -> Result<String, JsValue> {
-        let input_format = match format.to_lowercase().as_str() {
-            "aeh" => InputFormat::AEH,
-            "lanl" => InputFormat::LANL,
-            "regex" => InputFormat::Regex,
-            _ => InputFormat::Plain,
-        };
-
-        // Create a new network
-        let mut network = TransmissionNetwork::new();
-
-        // Parse CSV and build the network
-        network
-            .read_from_csv_str(csv_data, threshold, input_format)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let input_format = parse_format(format);
 
-        // Compute the network structure
-        network.compute_adjacency();
-        network.compute_clusters();
+        network_stats_internal(csv_data, threshold, input_format, false)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-        // Get stats as JSON
-        let stats = network.get_network_stats();
-        let json = serde_json::to_string(&stats).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    /// Get network statistics in JSON format, including all edge distances
+    #[wasm_bindgen]
+    pub fn get_network_stats_with_distances(
+        csv_data: &str,
+        threshold: f64,
+        format: &str,
+    ) -> Result<String, JsValue> {
+        let input_format = parse_format(format);
 
-        Ok(json)
+        network_stats_internal(csv_data, threshold, input_format, true)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
     }
     
     /// WASM bindings for the network annotator
@@ -111,3 +141,82 @@ pub fn build_network_internal(
     // Convert to JSON string
     network.to_json_string()
 }
+
+/// Build network in lenient mode and return `{"json": <network>, "warnings":
+/// [{"line": ..., "message": ...}]}`, where warnings list skipped rows and
+/// unparseable dates
+pub fn build_network_with_warnings_internal(
+    csv_data: &str,
+    threshold: f64,
+    format: InputFormat,
+) -> Result<String, NetworkError> {
+    // Create a new network that skips malformed rows instead of failing
+    let mut network = TransmissionNetwork::new();
+    network.lenient_parsing = true;
+
+    // Parse CSV and build the network
+    network.read_from_csv_str(csv_data, threshold, format)?;
+
+    // Compute the network structure
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let warnings: Vec<serde_json::Value> = network
+        .warnings()
+        .iter()
+        .map(|w| serde_json::json!({"line": w.line, "message": w.message}))
+        .collect();
+    let json = serde_json::to_value(network.to_json()).map_err(NetworkError::Json)?;
+
+    serde_json::to_string(&serde_json::json!({"json": json, "warnings": warnings}))
+        .map_err(NetworkError::Json)
+}
+
+/// Build network from edge-tuple JSON (`{"edges": [["A", "B", 0.01], ...]}`)
+/// and return JSON representation
+pub fn build_network_from_edge_tuples_internal(
+    edges_json: &str,
+    threshold: f64,
+) -> Result<String, NetworkError> {
+    let mut network = TransmissionNetwork::from_edge_tuples_json(edges_json, threshold)?;
+
+    // Compute the network structure
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // Convert to JSON string
+    network.to_json_string()
+}
+
+/// Build network and return its statistics as JSON, optionally including
+/// the distance of every edge (e.g. for histograms)
+pub fn network_stats_internal(
+    csv_data: &str,
+    threshold: f64,
+    format: InputFormat,
+    include_distances: bool,
+) -> Result<String, NetworkError> {
+    // Create a new network
+    let mut network = TransmissionNetwork::new();
+
+    // Parse CSV and build the network
+    network.read_from_csv_str(csv_data, threshold, format)?;
+
+    // Compute the network structure
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // Get stats, adding the raw distances if requested
+    let mut stats = network.get_network_stats();
+    if include_distances {
+        let distances: Vec<f64> = network
+            .edges
+            .iter()
+            .filter(|e| e.visible && !e.is_unsupported)
+            .map(|e| e.distance)
+            .collect();
+        stats.insert("edge_distances".to_string(), serde_json::json!(distances));
+    }
+
+    serde_json::to_string(&stats).map_err(NetworkError::Json)
+}
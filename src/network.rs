@@ -1,8 +1,21 @@
-use crate::parser::parse_patient_id;
-use crate::types::{Edge, InputFormat, NetworkError, ParsedPatient, Patient};
-use chrono::Utc;
+use crate::parser::{parse_patient_id, unparseable_date};
+use crate::types::{
+    AehFieldSchema, AttributeConflictPolicy, ClusterNaming, ColumnMapping, CsvLimits, DateBucket,
+    Edge, FilterReason, IdNormalization, IdOrdering, InputFormat, NetworkError, ParseWarning,
+    ParsedPatient, Patient, SequenceCountMode, ThresholdMode,
+};
+use crate::utils::{
+    describe_vector, levenshtein, percentile, round_to_significant_figures, SplitMix64,
+};
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet, VecDeque};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// Tolerance below which duplicate edge distances are considered equal
+const DUPLICATE_DISTANCE_EPSILON: f64 = 1e-9;
 
 /// The main network structure
 #[derive(Debug)]
@@ -11,6 +24,9 @@ pub struct TransmissionNetwork {
     pub nodes: HashMap<String, Patient>,
 
     /// All edges in the network
+    ///
+    /// After changing edges directly (e.g. `edge.visible`), call
+    /// `compute_adjacency` before `compute_clusters`.
     pub edges: Vec<Edge>,
 
     /// Adjacency list representation (node ID -> neighboring node IDs)
@@ -21,6 +37,217 @@ pub struct TransmissionNetwork {
 
     /// Network metadata for output
     pub metadata: HashMap<String, serde_json::Value>,
+
+    /// Edges whose support (optional 4th CSV column) falls below this cutoff
+    /// are marked unsupported and excluded from clustering
+    pub support_cutoff: Option<f64>,
+
+    /// Reject rows with more columns than node1,node2,distance[,support]
+    pub strict_columns: bool,
+
+    /// Read edge fields from the named header columns instead of
+    /// node1,node2,distance[,support]; the input must then have a header row,
+    /// and other columns are ignored
+    pub column_mapping: Option<ColumnMapping>,
+
+    /// Field layout used when parsing AEH-format IDs
+    pub aeh_schema: AehFieldSchema,
+
+    /// Also emit cluster sizes paired with cluster IDs, largest first
+    pub cluster_sizes_with_ids: bool,
+
+    /// Emit raw 0-based cluster IDs (singletons and unassigned nodes as -1)
+    /// instead of 1-based IDs
+    pub zero_index_clusters: bool,
+
+    /// Include hidden (invisible) edges in the JSON Edges section, flagged as removed
+    pub include_hidden_edges: bool,
+
+    /// Normalization applied to node IDs when parsing
+    pub id_normalization: IdNormalization,
+
+    /// Emit an edge `weight` array (1 - distance, clamped to [0, 1]) in the JSON output
+    pub include_edge_weights: bool,
+
+    /// Round edge lengths in the JSON output to this many significant figures
+    /// (full precision if None); stored distances are unaffected
+    pub distance_significant_figures: Option<usize>,
+
+    /// Definition used for the "Sequences used to make links" summary count
+    pub sequence_count_mode: SequenceCountMode,
+
+    /// Node IDs in the order they were first seen
+    pub node_order: Vec<String>,
+
+    /// Emit nodes in first-seen order in the JSON output instead of sorted by ID
+    pub preserve_node_order: bool,
+
+    /// Ordering used to normalize edge endpoints
+    pub id_ordering: IdOrdering,
+
+    /// Whether edges exactly at the threshold are kept
+    pub threshold_mode: ThresholdMode,
+
+    /// Also exclude edges with distance below this minimum (e.g. identical
+    /// sequences), keeping only the band [min_threshold, threshold]
+    pub min_threshold: Option<f64>,
+
+    /// Factor applied to each parsed distance (e.g. 0.01 when distances are percentages)
+    pub distance_scale: f64,
+
+    /// Include inferred edge directions and input orientations in the JSON Edges section
+    pub include_edge_direction: bool,
+
+    /// Cap the reported degree distribution at this degree; the last bin
+    /// counts all nodes with degree >= the cap
+    pub max_reported_degree: Option<usize>,
+
+    /// How differing attribute values for the same node across rows are resolved
+    pub attribute_conflict_policy: AttributeConflictPolicy,
+
+    /// Skip malformed rows (and rows with empty IDs) while reading, recording
+    /// them and unparseable dates as `warnings` instead of failing
+    pub lenient_parsing: bool,
+
+    /// Keep edges dropped at read time (all-edges mode) so `filtered_edges`
+    /// can report them
+    pub retain_filtered_edges: bool,
+
+    /// Report all singleton (degree 0) nodes under one pseudo-cluster, the
+    /// unassigned cluster ID, instead of individual cluster IDs
+    pub collapse_singletons: bool,
+
+    /// Emit `Nodes` as an array of per-node objects instead of parallel arrays
+    pub nodes_as_objects_output: bool,
+
+    /// Hardened reading mode for untrusted input: reject over-long lines and fields
+    pub csv_limits: Option<CsvLimits>,
+
+    /// Maximum number of nodes accepted while parsing input (no limit if None)
+    pub max_nodes: Option<usize>,
+
+    /// Maximum number of edges accepted while parsing input (no limit if None)
+    pub max_edges: Option<usize>,
+
+    /// Delimiter separating subject and sample in IDs (e.g. "~" in `P1~s1`);
+    /// when set, samples of one subject are folded into a single node
+    pub collapse_by: Option<String>,
+
+    /// Include a provenance block (version, input, settings) in the JSON output
+    pub include_provenance: bool,
+
+    /// Input file name reported in the provenance block
+    pub input_file: Option<String>,
+
+    /// SHA-256 (hex) of the input reported in the provenance block
+    pub input_sha256: Option<String>,
+
+    /// Optional scheme for naming clusters in the `cluster_names` output
+    pub cluster_naming: Option<ClusterNaming>,
+
+    /// Duplicate edges seen with materially different distances
+    /// (source, target, first distance, conflicting distance)
+    inconsistent_edges: Vec<(String, String, f64, f64)>,
+
+    /// Adjacency and degrees were left for `compute_adjacency` to build
+    /// (set by `read_from_csv_str_fast`)
+    defer_adjacency: bool,
+
+    /// Adjacency was rebuilt by `compute_adjacency` and no edge has been
+    /// added since; otherwise `compute_clusters` rebuilds it first. Direct
+    /// changes to `edges` can't be seen here.
+    adjacency_current: bool,
+
+    /// Edges dropped at read time, kept when `retain_filtered_edges` is set
+    dropped_edges: Vec<(String, String, f64, FilterReason)>,
+
+    /// Issues noticed while reading in lenient mode
+    warnings: Vec<ParseWarning>,
+
+    /// Attributes from a node-metadata sidecar, applied to nodes as they are
+    /// created (set by `read_node_metadata_csv`)
+    node_metadata: HashMap<String, Vec<(String, String)>>,
+}
+
+/// 1-based input line of a CSV record (0 if unknown)
+fn record_line(record: &csv::StringRecord) -> u64 {
+    record.position().map(|p| p.line()).unwrap_or(0)
+}
+
+/// Detect whether a CSV input starts with a header row - this is a heuristic
+fn csv_has_headers(csv_str: &str) -> bool {
+    csv_str
+        .lines()
+        .next()
+        .map(|first_line| {
+            let columns: Vec<&str> = first_line.split(',').collect();
+            columns.len() >= 3 && columns[2].trim() == "distance"
+        })
+        .unwrap_or(false)
+}
+
+/// Edges and node IDs collected from input rows before they are added
+struct StagedInput {
+    threshold: f64,
+    format: InputFormat,
+    edges: Vec<(ParsedPatient, ParsedPatient, f64, Option<f64>)>,
+    node_ids: Vec<String>,
+    seen_node_ids: HashSet<String>,
+    collapsed_edge_index: HashMap<(String, String), usize>,
+    dropped: Vec<(String, String, f64, FilterReason)>,
+    warnings: Vec<ParseWarning>,
+    date_warned_ids: HashSet<String>,
+    rows: usize,
+}
+
+impl StagedInput {
+    fn new(threshold: f64, format: InputFormat) -> Self {
+        StagedInput {
+            threshold,
+            format,
+            edges: Vec::new(),
+            node_ids: Vec::new(),
+            seen_node_ids: HashSet::new(),
+            collapsed_edge_index: HashMap::new(),
+            dropped: Vec::new(),
+            warnings: Vec::new(),
+            date_warned_ids: HashSet::new(),
+            rows: 0,
+        }
+    }
+}
+
+/// Positions of the edge fields in CSV records
+#[derive(Debug, Clone, Copy)]
+struct EdgeColumns {
+    source: usize,
+    target: usize,
+    distance: usize,
+    support: Option<usize>,
+}
+
+impl EdgeColumns {
+    /// node1,node2,distance[,support]
+    const DEFAULT: EdgeColumns = EdgeColumns {
+        source: 0,
+        target: 1,
+        distance: 2,
+        support: Some(3),
+    };
+
+    /// Number of columns a row needs to hold the required fields
+    fn required_len(&self) -> usize {
+        self.source.max(self.target).max(self.distance) + 1
+    }
+}
+
+/// A validated CSV row: (id1, id2, distance, support)
+type EdgeRow<'r> = (&'r str, &'r str, f64, Option<f64>);
+
+/// Edge-tuple JSON input: `{"edges": [["A", "B", 0.01], ...]}`
+#[derive(Deserialize)]
+struct EdgeTuplesInput {
+    edges: Vec<(String, String, f64)>,
 }
 
 /// A simple cluster representation for output
@@ -31,6 +258,58 @@ pub struct Cluster {
     pub size: usize,
 }
 
+/// A cluster with its member nodes and the edges between them
+#[derive(Debug, Clone)]
+pub struct ClusterDetail {
+    pub id: usize,
+    pub nodes: Vec<String>,
+    pub edges: Vec<Edge>,
+}
+
+/// Per-cluster summary statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterStatistics {
+    pub id: usize,
+    pub size: usize,
+    pub edge_count: usize,
+    /// Number of node triples in the cluster that are all pairwise linked
+    pub triangle_count: usize,
+}
+
+/// Summary of the cluster size distribution (singletons are not clusters)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterSizeSummary {
+    pub cluster_count: usize,
+    pub mean_size: f64,
+    pub median_size: f64,
+    pub max_size: usize,
+    /// Clusters of exactly two nodes
+    pub dyads: usize,
+    /// Nodes not in any cluster
+    pub singletons: usize,
+}
+
+/// Reproducibility details about how a network was built
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    pub input_file: Option<String>,
+    pub input_sha256: Option<String>,
+    pub version: String,
+    pub threshold: f64,
+    pub format: Option<String>,
+    pub created: String,
+    pub input_rows: usize,
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+/// A cluster ID paired with its size for output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterSize {
+    pub id: i64,
+    pub size: usize,
+}
+
 /// Output JSON format compatible with legacy HIVCluster output
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NetworkJSON {
@@ -46,6 +325,19 @@ pub struct TraceResults {
     pub multiple_sequences: MultipleSequences,
     #[serde(rename = "Cluster sizes")]
     pub cluster_sizes: Vec<usize>,
+    /// Intra-cluster edge counts, aligned with `cluster_sizes`
+    #[serde(default)]
+    pub cluster_edge_counts: Vec<usize>,
+    #[serde(
+        rename = "Cluster sizes with IDs",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cluster_sizes_with_ids: Option<Vec<ClusterSize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster_names: Option<BTreeMap<i64, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
     #[serde(rename = "HIV Stages")]
     pub hiv_stages: HashMap<String, usize>,
     #[serde(rename = "Directed Edges")]
@@ -70,6 +362,9 @@ pub struct NetworkSummary {
     pub sequences_used: usize,
     pub Clusters: usize,
     pub Singletons: usize,
+    /// Clusters of exactly two nodes
+    #[serde(rename = "Dyads", default)]
+    pub dyads: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -107,11 +402,16 @@ pub struct Settings {
     pub singletons: bool,
     pub compact_json: bool,
     pub created: String,
+    #[serde(rename = "sequences-used", default)]
+    pub sequences_used: String,
+    /// Whether singletons share one pseudo-cluster in `Nodes.cluster`
+    #[serde(rename = "collapsed-singletons", default)]
+    pub collapsed_singletons: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NodesOutput {
-    pub cluster: Vec<usize>,
+    pub cluster: Vec<i64>,
     pub id: Vec<String>,
     pub patient_attributes: Vec<serde_json::Value>,
 }
@@ -122,8 +422,16 @@ pub struct EdgesOutput {
     pub sequences: Vec<Vec<String>>,
     pub target: Vec<usize>,
     pub length: Vec<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<Vec<f64>>,
+    /// Inferred direction per edge relative to source -> target (1, -1, or 0 if unresolved)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Vec<i8>>,
+    /// Whether source/target were swapped from the input orientation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orientation_swapped: Option<Vec<bool>>,
     pub attributes: AttributeValues,
-    pub removed: DirectedValues,
+    pub removed: RemovedValues,
     pub support: SupportValues,
     pub source: Vec<usize>,
 }
@@ -134,6 +442,15 @@ pub struct DirectedValues {
     pub values: Vec<usize>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemovedValues {
+    pub keys: HashMap<String, bool>,
+    pub values: Vec<usize>,
+    /// Reason each removed edge was removed, keyed by its index in the Edges arrays
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub reasons: HashMap<String, String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AttributeValues {
     pub keys: HashMap<String, Vec<String>>,
@@ -163,9 +480,96 @@ impl TransmissionNetwork {
             adjacency: HashMap::new(),
             edge_lookup: HashMap::new(),
             metadata: HashMap::new(),
+            support_cutoff: None,
+            strict_columns: false,
+            column_mapping: None,
+            aeh_schema: AehFieldSchema::default(),
+            cluster_sizes_with_ids: false,
+            zero_index_clusters: false,
+            include_hidden_edges: false,
+            id_normalization: IdNormalization::default(),
+            include_edge_weights: false,
+            distance_significant_figures: None,
+            sequence_count_mode: SequenceCountMode::default(),
+            node_order: Vec::new(),
+            preserve_node_order: false,
+            id_ordering: IdOrdering::default(),
+            threshold_mode: ThresholdMode::default(),
+            min_threshold: None,
+            distance_scale: 1.0,
+            include_edge_direction: false,
+            max_reported_degree: None,
+            attribute_conflict_policy: AttributeConflictPolicy::default(),
+            lenient_parsing: false,
+            retain_filtered_edges: false,
+            collapse_singletons: false,
+            nodes_as_objects_output: false,
+            csv_limits: None,
+            max_nodes: None,
+            max_edges: None,
+            collapse_by: None,
+            include_provenance: false,
+            input_file: None,
+            input_sha256: None,
+            cluster_naming: None,
+            inconsistent_edges: Vec::new(),
+            defer_adjacency: false,
+            adjacency_current: false,
+            dropped_edges: Vec::new(),
+            warnings: Vec::new(),
+            node_metadata: HashMap::new(),
         }
     }
 
+    /// Clear all data and options, returning the network to its `new()` state
+    ///
+    /// Existing collection allocations are kept so the network can be reused
+    /// without reallocating.
+    pub fn clear(&mut self) {
+        let mut nodes = std::mem::take(&mut self.nodes);
+        let mut edges = std::mem::take(&mut self.edges);
+        let mut adjacency = std::mem::take(&mut self.adjacency);
+        let mut edge_lookup = std::mem::take(&mut self.edge_lookup);
+        let mut metadata = std::mem::take(&mut self.metadata);
+        let mut node_order = std::mem::take(&mut self.node_order);
+        let mut inconsistent_edges = std::mem::take(&mut self.inconsistent_edges);
+
+        nodes.clear();
+        edges.clear();
+        adjacency.clear();
+        edge_lookup.clear();
+        metadata.clear();
+        node_order.clear();
+        inconsistent_edges.clear();
+
+        *self = TransmissionNetwork {
+            nodes,
+            edges,
+            adjacency,
+            edge_lookup,
+            metadata,
+            node_order,
+            inconsistent_edges,
+            ..Self::new()
+        };
+    }
+
+    /// Read network data from a CSV string without maintaining adjacency or
+    /// degrees while reading
+    ///
+    /// Produces the same network as `read_from_csv_str`, but adjacency lists
+    /// and node degrees are only built by the next `compute_adjacency` call,
+    /// avoiding the double work of building them during the read as well.
+    pub fn read_from_csv_str_fast(
+        &mut self,
+        csv_str: &str,
+        distance_threshold: f64,
+        format: InputFormat,
+    ) -> Result<(), NetworkError> {
+        self.defer_adjacency = true;
+        self.read_from_csv_str(csv_str, distance_threshold, format)
+    }
+
     /// Read network data from a CSV string
     pub fn read_from_csv_str(
         &mut self,
@@ -173,94 +577,524 @@ impl TransmissionNetwork {
         distance_threshold: f64,
         format: InputFormat,
     ) -> Result<(), NetworkError> {
+        let mut reader = self.begin_csv_read(csv_str, distance_threshold)?;
+        let columns = self.edge_columns(&mut reader)?;
+
+        // First pass: track all node IDs and collect valid edges
+        let mut staged = StagedInput::new(distance_threshold, format);
+
+        for result in reader.records() {
+            let record = result?;
+            staged.rows += 1;
+
+            let row = self.parse_edge_row(&record, &columns);
+            self.stage_row(&mut staged, row, record_line(&record))?;
+        }
+
+        self.add_staged(staged)
+    }
+
+    /// Read network data from a CSV string, validating rows in parallel
+    ///
+    /// Records are parsed and validated across threads, then staged in input
+    /// order, so the network (including which duplicate edge wins) is
+    /// identical to `read_from_csv_str`. Malformed CSV is reported before any
+    /// row is validated.
+    #[cfg(feature = "parallel")]
+    pub fn read_from_csv_str_parallel(
+        &mut self,
+        csv_str: &str,
+        distance_threshold: f64,
+        format: InputFormat,
+    ) -> Result<(), NetworkError> {
+        use rayon::prelude::*;
+
+        let mut reader = self.begin_csv_read(csv_str, distance_threshold)?;
+        let columns = self.edge_columns(&mut reader)?;
+        let records = reader
+            .records()
+            .collect::<Result<Vec<csv::StringRecord>, _>>()?;
+
+        let rows: Vec<_> = records
+            .par_iter()
+            .map(|record| self.parse_edge_row(record, &columns))
+            .collect();
+
+        let mut staged = StagedInput::new(distance_threshold, format);
+        for (record, row) in records.iter().zip(rows) {
+            staged.rows += 1;
+            self.stage_row(&mut staged, row, record_line(record))?;
+        }
+
+        self.add_staged(staged)
+    }
+
+    /// Check CSV input against the configured limits, record the threshold,
+    /// and open a reader over it
+    fn begin_csv_read<'a>(
+        &mut self,
+        csv_str: &'a str,
+        distance_threshold: f64,
+    ) -> Result<csv::Reader<&'a [u8]>, NetworkError> {
         // Check for empty input
         if csv_str.trim().is_empty() {
             return Err(NetworkError::Format("Empty CSV input".to_string()));
         }
 
+        // In hardened mode, reject over-long lines before the CSV reader sees them
+        if let Some(limits) = self.csv_limits {
+            if let Some((line_idx, line)) = csv_str
+                .lines()
+                .enumerate()
+                .find(|(_, line)| line.len() > limits.max_line_length)
+            {
+                return Err(NetworkError::Format(format!(
+                    "Line {} is {} bytes long; the limit is {}",
+                    line_idx + 1,
+                    line.len(),
+                    limits.max_line_length
+                )));
+            }
+        }
+
         // Set threshold in metadata for later use
         self.metadata.insert(
             "threshold".to_string(),
             serde_json::json!(distance_threshold),
         );
 
-        // Try to detect if the CSV has headers - this is a heuristic
-        let has_headers = csv_str
-            .lines()
-            .next()
-            .map(|first_line| {
-                let columns: Vec<&str> = first_line.split(',').collect();
-                columns.len() >= 3 && columns[2].trim() == "distance"
-            })
-            .unwrap_or(false);
-
-        let mut reader = csv::ReaderBuilder::new()
+        Ok(csv::ReaderBuilder::new()
             .flexible(true)
-            .has_headers(has_headers) // Auto-detect headers
-            .from_reader(csv_str.as_bytes());
+            .has_headers(self.csv_has_headers(csv_str))
+            .from_reader(csv_str.as_bytes()))
+    }
 
-        // First pass: track all node IDs and collect valid edges
-        let mut edges_to_add = Vec::new();
-        let mut all_node_ids = HashSet::new();
+    /// Whether CSV input starts with a header row: always with a column
+    /// mapping, otherwise auto-detected
+    fn csv_has_headers(&self, csv_str: &str) -> bool {
+        self.column_mapping.is_some() || csv_has_headers(csv_str)
+    }
 
-        for result in reader.records() {
-            let record = result?;
+    /// Resolve the positions of the edge fields, using the header row when
+    /// a column mapping is set
+    fn edge_columns(&self, reader: &mut csv::Reader<&[u8]>) -> Result<EdgeColumns, NetworkError> {
+        let mapping = match &self.column_mapping {
+            Some(mapping) => mapping,
+            None => return Ok(EdgeColumns::DEFAULT),
+        };
 
-            if record.len() < 3 {
-                return Err(NetworkError::Format(
-                    "CSV row must have at least 3 columns: node1,node2,distance".to_string(),
-                ));
+        let headers = reader.headers()?;
+        let find = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim() == name)
+                .ok_or_else(|| {
+                    NetworkError::Format(format!("Column '{}' not found in CSV header", name))
+                })
+        };
+
+        Ok(EdgeColumns {
+            source: find(&mapping.source)?,
+            target: find(&mapping.target)?,
+            distance: find(&mapping.distance)?,
+            support: mapping.support.as_deref().map(find).transpose()?,
+        })
+    }
+
+    /// Stage one validated CSV row
+    ///
+    /// In lenient mode, malformed rows, self-loops, and rows with empty IDs
+    /// are skipped with a warning, and unparseable dates are reported.
+    fn stage_row(
+        &self,
+        staged: &mut StagedInput,
+        row: Result<Option<EdgeRow>, NetworkError>,
+        line: u64,
+    ) -> Result<(), NetworkError> {
+        let result = match row {
+            Ok(Some((id1, id2, distance, support))) => {
+                if self.lenient_parsing {
+                    for id in [id1, id2] {
+                        self.check_date(staged, id, line);
+                    }
+                }
+                self.stage_edge(staged, id1, id2, distance, support)
+            }
+            Ok(None) => {
+                if self.lenient_parsing {
+                    staged.warnings.push(ParseWarning {
+                        line,
+                        message: "skipped row with an empty node ID".to_string(),
+                    });
+                }
+                Ok(())
             }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Err(e @ (NetworkError::Format(_) | NetworkError::SelfLoop)) if self.lenient_parsing => {
+                staged.warnings.push(ParseWarning {
+                    line,
+                    message: format!("skipped row: {}", e),
+                });
+                Ok(())
+            }
+            other => other,
+        }
+    }
+
+    /// Warn (once per ID) about a date field in an ID that cannot be parsed
+    fn check_date(&self, staged: &mut StagedInput, id: &str, line: u64) {
+        if staged.date_warned_ids.contains(id) {
+            return;
+        }
+        if let Some(field) = unparseable_date(id, staged.format, &self.aeh_schema) {
+            staged.date_warned_ids.insert(id.to_string());
+            staged.warnings.push(ParseWarning {
+                line,
+                message: format!("could not parse date '{}' in ID '{}'", field, id),
+            });
+        }
+    }
 
-            // Extract values from record
-            let id1 = record.get(0).unwrap_or("").trim();
-            let id2 = record.get(1).unwrap_or("").trim();
+    /// Validate one CSV record and extract (id1, id2, distance, support)
+    ///
+    /// Returns `None` for rows that are skipped (blank IDs).
+    fn parse_edge_row<'r>(
+        &self,
+        record: &'r csv::StringRecord,
+        columns: &EdgeColumns,
+    ) -> Result<Option<EdgeRow<'r>>, NetworkError> {
+        if record.len() < columns.required_len() {
+            if self.column_mapping.is_some() {
+                return Err(NetworkError::Format(format!(
+                    "CSV row at line {} is missing mapped columns",
+                    record_line(record)
+                )));
+            }
+            return Err(NetworkError::Format(
+                "CSV row must have at least 3 columns: node1,node2,distance".to_string(),
+            ));
+        }
 
-            if id1.is_empty() || id2.is_empty() {
-                continue; // Skip rows with empty IDs
+        // In hardened mode, reject over-long fields (e.g. runaway quoting)
+        if let Some(limits) = self.csv_limits {
+            if let Some(field) = record.iter().find(|f| f.len() > limits.max_field_length) {
+                let line = record_line(record);
+                return Err(NetworkError::Format(format!(
+                    "CSV field at line {} is {} bytes long; the limit is {}",
+                    line,
+                    field.len(),
+                    limits.max_field_length
+                )));
             }
+        }
+
+        // In strict mode, extra columns most likely indicate a malformed row
+        // (mapped inputs are expected to carry other columns)
+        if self.strict_columns && self.column_mapping.is_none() && record.len() > 4 {
+            let line = record_line(record);
+            return Err(NetworkError::Format(format!(
+                "CSV row at line {} has {} columns; expected node1,node2,distance[,support]",
+                line,
+                record.len()
+            )));
+        }
+
+        // Extract values from record
+        let id1 = record.get(columns.source).unwrap_or("").trim();
+        let id2 = record.get(columns.target).unwrap_or("").trim();
 
-            // Track all node IDs for singleton detection
-            all_node_ids.insert(id1.to_string());
-            all_node_ids.insert(id2.to_string());
+        if is_blank_id(id1) || is_blank_id(id2) {
+            return Ok(None); // Skip rows with empty IDs
+        }
+
+        let distance_field = record.get(columns.distance).unwrap_or("");
+        let distance = match distance_field.trim().parse::<f64>() {
+            Ok(d) => d,
+            Err(_) => {
+                return Err(NetworkError::Format(format!(
+                    "Invalid distance value: {}",
+                    distance_field
+                )));
+            }
+        };
 
-            let distance = match record.get(2).unwrap_or("").trim().parse::<f64>() {
-                Ok(d) => d,
+        // Optional support column (the 4th by default)
+        let support = match columns.support.and_then(|i| record.get(i)).map(str::trim) {
+            Some(value) if !value.is_empty() => match value.parse::<f64>() {
+                Ok(s) => Some(s),
                 Err(_) => {
                     return Err(NetworkError::Format(format!(
-                        "Invalid distance value: {}",
-                        record.get(2).unwrap_or("")
+                        "Invalid support value: {}",
+                        value
                     )));
                 }
-            };
+            },
+            _ => None,
+        };
+
+        Ok(Some((id1, id2, distance, support)))
+    }
+
+    /// Read a node-metadata sidecar CSV of `id,attr1,attr2,...` with a header
+    ///
+    /// Attributes are merged into `named_attributes` of nodes already in the
+    /// network, and of matching nodes created by later reads, using the
+    /// `attribute_conflict_policy`. IDs are normalized like plain node IDs;
+    /// rows with blank IDs and empty values are skipped.
+    pub fn read_node_metadata_csv(&mut self, csv_str: &str) -> Result<(), NetworkError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(csv_str.as_bytes());
+
+        let headers = reader.headers()?.clone();
+        if headers.len() < 2 {
+            return Err(NetworkError::Format(
+                "Node metadata CSV needs a header with an id column and at least one attribute"
+                    .to_string(),
+            ));
+        }
 
-            // Skip edges with distance greater than threshold
-            if distance > distance_threshold {
+        let mut loaded: Vec<(String, Vec<(String, String)>)> = Vec::new();
+        for result in reader.records() {
+            let record = result?;
+            let id = record.get(0).unwrap_or("").trim();
+            if is_blank_id(id) {
                 continue;
             }
 
-            // Skip self loops (same ID for both nodes)
-            if id1 == id2 {
-                return Err(NetworkError::SelfLoop);
+            let patient = parse_patient_id(
+                id,
+                InputFormat::Plain,
+                None,
+                &self.aeh_schema,
+                self.id_normalization,
+            )?;
+            let attributes = headers
+                .iter()
+                .zip(record.iter())
+                .skip(1)
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .filter(|(_, value)| !value.is_empty())
+                .collect();
+            loaded.push((patient.id, attributes));
+        }
+
+        for (id, attributes) in loaded {
+            if let Some(node) = self.nodes.get_mut(&id) {
+                for (key, value) in &attributes {
+                    node.merge_named_attribute(key, value, self.attribute_conflict_policy)?;
+                }
+            }
+            self.node_metadata.entry(id).or_default().extend(attributes);
+        }
+
+        Ok(())
+    }
+
+    /// Build a network from (node1, node2, distance) tuples
+    ///
+    /// Applies the same threshold, self-loop, and duplicate rules as
+    /// `read_from_csv_str` with plain IDs. Adjacency and clusters are not
+    /// computed.
+    pub fn from_edges(
+        edges: &[(String, String, f64)],
+        threshold: f64,
+    ) -> Result<TransmissionNetwork, NetworkError> {
+        let mut network = TransmissionNetwork::new();
+        network
+            .metadata
+            .insert("threshold".to_string(), serde_json::json!(threshold));
+
+        let mut staged = StagedInput::new(threshold, InputFormat::Plain);
+        for (id1, id2, distance) in edges {
+            staged.rows += 1;
+            network.stage_edge(&mut staged, id1.trim(), id2.trim(), *distance, None)?;
+        }
+
+        network.add_staged(staged)?;
+        Ok(network)
+    }
+
+    /// Build a network from edge-tuple JSON of the form
+    /// `{"edges": [["A", "B", 0.01], ...]}`
+    ///
+    /// Applies the same rules as `from_edges`. Adjacency and clusters are not
+    /// computed.
+    pub fn from_edge_tuples_json(
+        json: &str,
+        threshold: f64,
+    ) -> Result<TransmissionNetwork, NetworkError> {
+        let input: EdgeTuplesInput = serde_json::from_str(json).map_err(NetworkError::Json)?;
+        Self::from_edges(&input.edges, threshold)
+    }
+
+    /// Validate one input edge and collect it (and its node IDs) for adding
+    fn stage_edge(
+        &self,
+        staged: &mut StagedInput,
+        id1: &str,
+        id2: &str,
+        distance: f64,
+        support: Option<f64>,
+    ) -> Result<(), NetworkError> {
+        let format = staged.format;
+
+        // Blank IDs never become nodes, even via the singleton tracking below
+        if is_blank_id(id1) || is_blank_id(id2) {
+            return Ok(()); // Skip rows with empty IDs
+        }
+
+        // Track all node IDs for singleton detection
+        for id in [id1, id2] {
+            if staged.seen_node_ids.insert(id.to_string()) {
+                staged.node_ids.push(id.to_string());
+            }
+        }
+
+        // Stop early rather than materializing an oversized network
+        if let Some(max_nodes) = self.max_nodes {
+            if staged.node_ids.len() > max_nodes {
+                return Err(NetworkError::SizeLimit(format!(
+                    "more than {} nodes in input",
+                    max_nodes
+                )));
+            }
+        }
+
+        // Distances are scaled before the threshold comparison (e.g. 0.01 for percentages)
+        let distance = distance * self.distance_scale;
+
+        // Skip edges that do not pass the threshold, or fall below the minimum
+        let filter_reason = if !self.threshold_mode.passes(distance, staged.threshold) {
+            Some(FilterReason::AboveThreshold)
+        } else if self.min_threshold.is_some_and(|min| distance < min) {
+            Some(FilterReason::BelowMinThreshold)
+        } else {
+            None
+        };
+        if let Some(reason) = filter_reason {
+            if self.retain_filtered_edges && id1 != id2 {
+                let patient1 = self.collapse_subject(parse_patient_id(
+                    id1,
+                    format,
+                    None,
+                    &self.aeh_schema,
+                    self.id_normalization,
+                )?);
+                let patient2 = self.collapse_subject(parse_patient_id(
+                    id2,
+                    format,
+                    None,
+                    &self.aeh_schema,
+                    self.id_normalization,
+                )?);
+                staged
+                    .dropped
+                    .push((patient1.id, patient2.id, distance, reason));
+            }
+            return Ok(());
+        }
+
+        // Skip self loops (same ID for both nodes)
+        if id1 == id2 {
+            return Err(NetworkError::SelfLoop);
+        }
+
+        // Parse node IDs
+        let patient1 = self.collapse_subject(parse_patient_id(
+            id1,
+            format,
+            None,
+            &self.aeh_schema,
+            self.id_normalization,
+        )?);
+        let patient2 = self.collapse_subject(parse_patient_id(
+            id2,
+            format,
+            None,
+            &self.aeh_schema,
+            self.id_normalization,
+        )?);
+
+        // Samples of the same subject don't link the subject to itself; the
+        // edge is built as `add_edge` will, so both share one self-loop check
+        let edge = match Edge::try_new_with_ordering(
+            patient1.id.clone(),
+            patient2.id.clone(),
+            patient1.date,
+            patient2.date,
+            distance,
+            self.id_ordering,
+        )? {
+            Some(edge) => edge,
+            None => return Ok(()),
+        };
+
+        // When collapsing, keep only the closest pair of samples between two subjects
+        if self.collapse_by.is_some() {
+            let key = edge.get_key();
+            if let Some(&idx) = staged.collapsed_edge_index.get(&key) {
+                if distance < staged.edges[idx].2 {
+                    staged.edges[idx] = (patient1, patient2, distance, support);
+                }
+                return Ok(());
             }
+            staged.collapsed_edge_index.insert(key, staged.edges.len());
+        }
 
-            // Parse node IDs
-            let patient1 = parse_patient_id(id1, format, None)?;
-            let patient2 = parse_patient_id(id2, format, None)?;
+        // Collect this edge for later addition
+        staged.edges.push((patient1, patient2, distance, support));
 
-            // Collect this edge for later addition
-            edges_to_add.push((patient1, patient2, distance));
+        if let Some(max_edges) = self.max_edges {
+            if staged.edges.len() > max_edges {
+                return Err(NetworkError::SizeLimit(format!(
+                    "more than {} edges within the threshold",
+                    max_edges
+                )));
+            }
         }
 
-        // Add all nodes first (including those without edges)
-        for id in all_node_ids {
-            let parsed_node = parse_patient_id(&id, format, None)?;
+        Ok(())
+    }
+
+    /// Add staged nodes (including those without edges) and edges to the network
+    fn add_staged(&mut self, staged: StagedInput) -> Result<(), NetworkError> {
+        let format = staged.format;
+
+        // Record input details for provenance
+        self.metadata.insert(
+            "format".to_string(),
+            serde_json::json!(format!("{:?}", format)),
+        );
+        self.metadata
+            .insert("input_rows".to_string(), serde_json::json!(staged.rows));
+
+        self.dropped_edges.extend(staged.dropped);
+        self.warnings.extend(staged.warnings);
+
+        // Add all nodes first (including those without edges); in lenient
+        // mode, IDs that failed to parse were already reported with their row
+        for id in staged.node_ids {
+            let parsed_node = match parse_patient_id(
+                &id,
+                format,
+                None,
+                &self.aeh_schema,
+                self.id_normalization,
+            ) {
+                Ok(parsed) => self.collapse_subject(parsed),
+                Err(_) if self.lenient_parsing => continue,
+                Err(e) => return Err(e),
+            };
             self.add_node(&parsed_node)?;
         }
 
         // Now add all valid edges
-        for (patient1, patient2, distance) in edges_to_add {
-            self.add_edge(patient1, patient2, distance)?;
+        for (patient1, patient2, distance, support) in staged.edges {
+            self.add_edge(patient1, patient2, distance, support)?;
         }
 
         self.update_stats();
@@ -268,8 +1102,25 @@ impl TransmissionNetwork {
         Ok(())
     }
 
+    /// Fold a sample ID into its subject ID when `collapse_by` is set
+    fn collapse_subject(&self, mut patient: ParsedPatient) -> ParsedPatient {
+        if let Some(delimiter) = self.collapse_by.as_deref().filter(|d| !d.is_empty()) {
+            if let Some((subject, _)) = patient.id.split_once(delimiter) {
+                if !subject.is_empty() {
+                    patient.id = subject.to_string();
+                }
+            }
+        }
+        patient
+    }
+
     /// Add a node to the network or update existing node
     fn add_node(&mut self, patient_data: &ParsedPatient) -> Result<(), NetworkError> {
+        // Track first-seen order of new nodes
+        if !self.nodes.contains_key(&patient_data.id) {
+            self.node_order.push(patient_data.id.clone());
+        }
+
         // Add or update node
         let node = self
             .nodes
@@ -279,15 +1130,24 @@ impl TransmissionNetwork {
         // Update node data
         node.add_date(patient_data.date);
 
-        // Add any attributes
+        // Add any attributes, resolving conflicts with earlier rows
         for (key, value) in &patient_data.attributes {
-            node.add_named_attribute(key, Some(value.clone()));
+            node.merge_named_attribute(key, value, self.attribute_conflict_policy)?;
+        }
+
+        // Add any sidecar metadata for this node
+        if let Some(attributes) = self.node_metadata.get(&patient_data.id) {
+            for (key, value) in attributes {
+                node.merge_named_attribute(key, value, self.attribute_conflict_policy)?;
+            }
         }
 
         // Initialize adjacency list if needed
-        self.adjacency
-            .entry(patient_data.id.clone())
-            .or_insert_with(Vec::new);
+        if !self.defer_adjacency {
+            self.adjacency
+                .entry(patient_data.id.clone())
+                .or_insert_with(Vec::new);
+        }
 
         Ok(())
     }
@@ -298,10 +1158,12 @@ impl TransmissionNetwork {
         patient1: ParsedPatient,
         patient2: ParsedPatient,
         distance: f64,
+        support: Option<f64>,
     ) -> Result<(), NetworkError> {
         // Ensure nodes exist
         self.add_node(&patient1)?;
         self.add_node(&patient2)?;
+        self.adjacency_current = false;
 
         // Check for self-loops
         if patient1.id == patient2.id {
@@ -309,14 +1171,22 @@ impl TransmissionNetwork {
         }
 
         // Create edge
-        let edge = Edge::new(
+        let mut edge = Edge::new_with_ordering(
             patient1.id.clone(),
             patient2.id.clone(),
             patient1.date,
             patient2.date,
             distance,
+            self.id_ordering,
         )?;
 
+        // Flag edges whose support falls below the cutoff
+        edge.support = support;
+        edge.is_unsupported = matches!(
+            (support, self.support_cutoff),
+            (Some(s), Some(cutoff)) if s < cutoff
+        );
+
         // Check if this edge already exists
         let edge_key = edge.get_key();
         if self.edge_lookup.contains_key(&edge_key) {
@@ -324,43 +1194,231 @@ impl TransmissionNetwork {
             let existing_edge_idx = self.edge_lookup[&edge_key];
             let existing_edge = &self.edges[existing_edge_idx];
 
-            if distance < existing_edge.distance {
-                // Replace with new edge that has smaller distance
-                self.edges[existing_edge_idx] = edge;
+            // Record inconsistent input for data-quality reporting
+            if (distance - existing_edge.distance).abs() > DUPLICATE_DISTANCE_EPSILON {
+                self.inconsistent_edges.push((
+                    edge.source_id.clone(),
+                    edge.target_id.clone(),
+                    existing_edge.distance,
+                    distance,
+                ));
             }
 
-            return Ok(());
+            if distance < existing_edge.distance {
+                // The replacement may differ in support, which connects or
+                // disconnects the pair
+                let was_unsupported = existing_edge.is_unsupported;
+                if !self.defer_adjacency && was_unsupported != edge.is_unsupported {
+                    if edge.is_unsupported {
+                        self.disconnect_nodes(&patient1.id, &patient2.id);
+                    } else {
+                        self.connect_nodes(&patient1.id, &patient2.id);
+                    }
+                }
+
+                // Replace with new edge that has smaller distance
+                self.edges[existing_edge_idx] = edge;
+            }
+
+            return Ok(());
+        }
+
+        // Unsupported edges are kept for reporting but don't connect nodes;
+        // with deferred adjacency, connections are built by compute_adjacency
+        if edge.is_unsupported || self.defer_adjacency {
+            let edge_idx = self.edges.len();
+            self.edge_lookup.insert(edge_key, edge_idx);
+            self.edges.push(edge);
+            return Ok(());
         }
 
-        // Add edge to the adjacency lists using original patient IDs
-        // (not the normalized edge IDs)
+        // Add edge to the adjacency lists and degrees using original patient
+        // IDs (not the normalized edge IDs)
+        self.connect_nodes(&patient1.id, &patient2.id);
+
+        // Store edge
+        let edge_idx = self.edges.len();
+        self.edge_lookup.insert(edge_key, edge_idx);
+        self.edges.push(edge);
+
+        Ok(())
+    }
+
+    /// Link two nodes in the adjacency lists and count the link in their degrees
+    fn connect_nodes(&mut self, id1: &str, id2: &str) {
         self.adjacency
-            .entry(patient1.id.clone())
+            .entry(id1.to_string())
             .or_insert_with(Vec::new)
-            .push(patient2.id.clone());
+            .push(id2.to_string());
 
         self.adjacency
-            .entry(patient2.id.clone())
+            .entry(id2.to_string())
             .or_insert_with(Vec::new)
-            .push(patient1.id.clone());
+            .push(id1.to_string());
 
-        // Update node degrees using original patient IDs
-        if let Some(node) = self.nodes.get_mut(&patient1.id) {
-            node.increment_degree();
+        for id in [id1, id2] {
+            if let Some(node) = self.nodes.get_mut(id) {
+                node.increment_degree();
+            }
         }
+    }
 
-        if let Some(node) = self.nodes.get_mut(&patient2.id) {
-            node.increment_degree();
+    /// Undo `connect_nodes`, removing one link between two nodes
+    fn disconnect_nodes(&mut self, id1: &str, id2: &str) {
+        for (id, neighbor) in [(id1, id2), (id2, id1)] {
+            if let Some(neighbors) = self.adjacency.get_mut(id) {
+                if let Some(pos) = neighbors.iter().position(|n| n == neighbor) {
+                    neighbors.remove(pos);
+                }
+            }
+            if let Some(node) = self.nodes.get_mut(id) {
+                node.degree = node.degree.saturating_sub(1);
+            }
         }
+    }
 
-        // Store edge
-        let edge_idx = self.edges.len();
-        self.edge_lookup.insert(edge_key, edge_idx);
-        self.edges.push(edge);
+    /// Relabel nodes using an old ID -> new ID mapping
+    ///
+    /// Node IDs are rewritten in `nodes`, `edges` (re-normalized) and `adjacency`,
+    /// and `edge_lookup` is rebuilt. If several nodes map to the same ID they are
+    /// merged when `merge_collisions` is set, and an error is returned otherwise.
+    /// Named attributes of merged nodes are combined under
+    /// `attribute_conflict_policy`, in ID order.
+    /// Edges that collapse into self-loops after merging are dropped. Clusters
+    /// should be recomputed afterwards.
+    pub fn rename_nodes(
+        &mut self,
+        mapping: &HashMap<String, String>,
+        merge_collisions: bool,
+    ) -> Result<(), NetworkError> {
+        let new_id = |id: &str| mapping.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+        // Detect collisions before modifying anything
+        if !merge_collisions {
+            let mut seen: HashMap<String, &String> = HashMap::new();
+            for old_id in self.nodes.keys() {
+                if let Some(other) = seen.insert(new_id(old_id), old_id) {
+                    return Err(NetworkError::Format(format!(
+                        "Renaming would merge nodes '{}' and '{}' into '{}'",
+                        other,
+                        old_id,
+                        new_id(old_id)
+                    )));
+                }
+            }
+        }
+
+        // Merge nodes into a new map first, so an attribute conflict leaves
+        // the network untouched
+        let mut old_nodes: Vec<&Patient> = self.nodes.values().collect();
+        old_nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut nodes: HashMap<String, Patient> = HashMap::with_capacity(old_nodes.len());
+        for node in old_nodes {
+            let mut node = node.clone();
+            node.id = new_id(&node.id);
+            node.degree = 0;
+            match nodes.get_mut(&node.id) {
+                Some(existing) => {
+                    for date in node.dates {
+                        existing.add_date(date);
+                    }
+                    existing.attributes.extend(node.attributes);
+                    let mut named: Vec<(String, String)> =
+                        node.named_attributes.into_iter().collect();
+                    named.sort();
+                    for (key, value) in named {
+                        existing.merge_named_attribute(
+                            &key,
+                            &value,
+                            self.attribute_conflict_policy,
+                        )?;
+                    }
+                    for (key, values) in node.collected_attributes {
+                        let collected = existing.collected_attributes.entry(key).or_default();
+                        for value in values {
+                            if !collected.contains(&value) {
+                                collected.push(value);
+                            }
+                        }
+                    }
+                }
+                None => {
+                    nodes.insert(node.id.clone(), node);
+                }
+            }
+        }
+        self.nodes = nodes;
+
+        // Keep first-seen order
+        let mut seen = HashSet::new();
+        self.node_order = self
+            .node_order
+            .iter()
+            .map(|id| new_id(id))
+            .filter(|id| seen.insert(id.clone()))
+            .collect();
+
+        // Rebuild edges with the new endpoints
+        let old_edges = std::mem::take(&mut self.edges);
+        self.edge_lookup.clear();
+        for old_edge in old_edges {
+            let (original_source, original_target) = old_edge.original_orientation();
+            let (source_date, target_date) = if old_edge.orientation_swapped {
+                (old_edge.target_date, old_edge.source_date)
+            } else {
+                (old_edge.source_date, old_edge.target_date)
+            };
+
+            let mut edge = match Edge::try_new_with_ordering(
+                new_id(original_source),
+                new_id(original_target),
+                source_date,
+                target_date,
+                old_edge.distance,
+                self.id_ordering,
+            )? {
+                Some(edge) => edge,
+                None => continue, // Endpoints were merged
+            };
+            edge.visible = old_edge.visible;
+            edge.attributes = old_edge.attributes;
+            edge.sequences = old_edge.sequences;
+            edge.support = old_edge.support;
+            edge.is_unsupported = old_edge.is_unsupported;
+
+            // Merged nodes may produce duplicate edges; keep the smaller distance
+            let edge_key = edge.get_key();
+            if let Some(&existing_idx) = self.edge_lookup.get(&edge_key) {
+                if edge.distance < self.edges[existing_idx].distance {
+                    self.edges[existing_idx] = edge;
+                }
+                continue;
+            }
+
+            self.edge_lookup.insert(edge_key, self.edges.len());
+            self.edges.push(edge);
+        }
+
+        // Recompute degrees and adjacency
+        for edge in self.edges.iter().filter(|e| !e.is_unsupported) {
+            for id in [&edge.source_id, &edge.target_id] {
+                if let Some(node) = self.nodes.get_mut(id) {
+                    node.increment_degree();
+                }
+            }
+        }
+        self.compute_adjacency();
+        self.update_stats();
 
         Ok(())
     }
 
+    /// Duplicate edges that appeared in the input with materially different
+    /// distances, as (source, target, first distance, conflicting distance)
+    pub fn inconsistent_edges(&self) -> Vec<(String, String, f64, f64)> {
+        self.inconsistent_edges.clone()
+    }
+
     /// Update network statistics
     fn update_stats(&mut self) {
         self.metadata.insert(
@@ -386,7 +1444,7 @@ impl TransmissionNetwork {
 
         // Add edges to adjacency lists
         for edge in &self.edges {
-            if !edge.visible {
+            if !edge.visible || edge.is_unsupported {
                 continue;
             }
 
@@ -401,10 +1459,30 @@ impl TransmissionNetwork {
 
             self.adjacency.entry(id2).or_insert_with(Vec::new).push(id1);
         }
+
+        // Degrees follow the rebuilt adjacency, so edges hidden since the read
+        // (and degrees skipped by a fast read) are accounted for
+        for (id, node) in self.nodes.iter_mut() {
+            node.degree = self.adjacency.get(id).map_or(0, Vec::len);
+        }
+        self.defer_adjacency = false;
+        self.adjacency_current = true;
     }
 
     /// Identify connected components (clusters) in the network
+    ///
+    /// Adjacency built incrementally while reading (or deferred by
+    /// `read_from_csv_str_fast`) is rebuilt with `compute_adjacency` first,
+    /// so calling this directly after a read gives the same clusters.
+    ///
+    /// Changes made directly to `edges` (such as setting `edge.visible`)
+    /// are not detected: call `compute_adjacency` after them, otherwise
+    /// clusters follow the adjacency from before the change.
     pub fn compute_clusters(&mut self) {
+        if !self.adjacency_current {
+            self.compute_adjacency();
+        }
+
         // Reset all cluster assignments
         for node in self.nodes.values_mut() {
             node.cluster_id = None;
@@ -413,26 +1491,30 @@ impl TransmissionNetwork {
         let mut cluster_id = 0;
         let mut visited = HashSet::new();
 
+        // Seed traversals in sorted ID order so cluster IDs are stable across runs
+        let mut node_ids: Vec<String> = self.nodes.keys().cloned().collect();
+        node_ids.sort();
+
         // First, assign clusters to connected nodes
-        for node_id in self.nodes.keys().cloned().collect::<Vec<String>>() {
-            if visited.contains(&node_id) {
+        for node_id in &node_ids {
+            if visited.contains(node_id) {
                 continue;
             }
 
             // Skip singleton nodes (they'll be processed separately)
-            if let Some(node) = self.nodes.get(&node_id) {
+            if let Some(node) = self.nodes.get(node_id) {
                 if node.degree == 0 {
                     continue;
                 }
             }
 
             // BFS to find all nodes in this cluster
-            self.breadth_first_traverse(&node_id, cluster_id, &mut visited);
+            self.breadth_first_traverse(node_id, cluster_id, &mut visited);
             cluster_id += 1;
         }
 
         // Now assign singleton nodes to their own clusters
-        for node_id in self.nodes.keys().cloned().collect::<Vec<String>>() {
+        for node_id in node_ids {
             if visited.contains(&node_id) {
                 continue;
             }
@@ -498,6 +1580,66 @@ impl TransmissionNetwork {
         }
     }
 
+    /// Identify clusters while preserving IDs from a previous assignment
+    ///
+    /// Clusters whose membership is unchanged keep their previous ID; new or
+    /// modified clusters get fresh IDs that don't collide with any previous one.
+    pub fn compute_clusters_stable(&mut self, previous: &HashMap<String, usize>) {
+        self.compute_clusters();
+
+        // Group previous assignment by cluster ID into sorted member lists
+        let mut previous_clusters: HashMap<usize, Vec<String>> = HashMap::new();
+        for (node_id, &cluster_id) in previous {
+            previous_clusters
+                .entry(cluster_id)
+                .or_default()
+                .push(node_id.clone());
+        }
+        let mut previous_ids_by_members: HashMap<Vec<String>, usize> = HashMap::new();
+        for (cluster_id, mut members) in previous_clusters {
+            members.sort();
+            previous_ids_by_members.insert(members, cluster_id);
+        }
+
+        // Fresh IDs start past anything used previously
+        let mut next_id = previous.values().max().map(|&id| id + 1).unwrap_or(0);
+
+        // Process new clusters in a deterministic order
+        let mut current_clusters: Vec<Vec<String>> = self
+            .retrieve_clusters(true)
+            .into_values()
+            .map(|mut members| {
+                members.sort();
+                members
+            })
+            .collect();
+        current_clusters.sort();
+
+        for members in current_clusters {
+            let cluster_id = match previous_ids_by_members.get(&members) {
+                Some(&id) => id,
+                None => {
+                    next_id += 1;
+                    next_id - 1
+                }
+            };
+
+            for node_id in &members {
+                if let Some(node) = self.nodes.get_mut(node_id) {
+                    node.cluster_id = Some(cluster_id);
+                }
+            }
+        }
+    }
+
+    /// Get the current cluster assignment as a map of node ID -> cluster ID
+    pub fn cluster_assignment(&self) -> HashMap<String, usize> {
+        self.nodes
+            .iter()
+            .filter_map(|(id, node)| node.cluster_id.map(|cluster_id| (id.clone(), cluster_id)))
+            .collect()
+    }
+
     /// Retrieve all clusters as a map of cluster ID -> list of node IDs
     pub fn retrieve_clusters(&self, include_singletons: bool) -> HashMap<usize, Vec<String>> {
         let mut clusters: HashMap<usize, Vec<String>> = HashMap::new();
@@ -516,144 +1658,1092 @@ impl TransmissionNetwork {
         clusters
     }
 
-    /// Extract nodes that have no connections (singletons)
-    pub fn extract_singleton_nodes(&self) -> Vec<String> {
-        self.nodes
+    /// Get every cluster with its sorted node IDs and intra-cluster edges
+    ///
+    /// Covers clusters of connected nodes (singletons are omitted), ordered by
+    /// cluster ID. Only visible, supported edges are included.
+    pub fn clusters_detailed(&self) -> Vec<ClusterDetail> {
+        let mut edges_by_cluster: HashMap<usize, Vec<Edge>> = HashMap::new();
+        for edge in self.edges.iter().filter(|e| e.visible && !e.is_unsupported) {
+            let source_cluster = self.nodes.get(&edge.source_id).and_then(|n| n.cluster_id);
+            let target_cluster = self.nodes.get(&edge.target_id).and_then(|n| n.cluster_id);
+            if let (Some(source_cluster), Some(target_cluster)) = (source_cluster, target_cluster) {
+                if source_cluster == target_cluster {
+                    edges_by_cluster
+                        .entry(source_cluster)
+                        .or_default()
+                        .push(edge.clone());
+                }
+            }
+        }
+
+        let mut details: Vec<ClusterDetail> = self
+            .retrieve_clusters(false)
+            .into_iter()
+            .map(|(id, mut nodes)| {
+                nodes.sort();
+                ClusterDetail {
+                    id,
+                    nodes,
+                    edges: edges_by_cluster.remove(&id).unwrap_or_default(),
+                }
+            })
+            .collect();
+        details.sort_by_key(|detail| detail.id);
+        details
+    }
+
+    /// Get the issues recorded while reading in lenient mode, in input order
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Get every edge left out of clustering, with the reason it was filtered
+    ///
+    /// Edges dropped at read time (threshold and minimum threshold) are only
+    /// reported when `retain_filtered_edges` was set before reading; they come
+    /// first, in input order, followed by loaded edges that are invisible or
+    /// unsupported.
+    pub fn filtered_edges(&self) -> Vec<(String, String, f64, FilterReason)> {
+        let mut filtered = self.dropped_edges.clone();
+        for edge in &self.edges {
+            let reason = if !edge.visible {
+                FilterReason::Invisible
+            } else if edge.is_unsupported {
+                FilterReason::LowSupport
+            } else {
+                continue;
+            };
+            filtered.push((
+                edge.source_id.clone(),
+                edge.target_id.clone(),
+                edge.distance,
+                reason,
+            ));
+        }
+        filtered
+    }
+
+    /// Get size, edge count and triangle count for every cluster
+    ///
+    /// Covers the same clusters as `clusters_detailed`, ordered by cluster ID.
+    /// Triangles are counted from the adjacency lists, so each one is counted
+    /// once regardless of edge orientation.
+    pub fn cluster_statistics(&self) -> Vec<ClusterStatistics> {
+        let neighbors: HashMap<&str, HashSet<&str>> = self
+            .adjacency
             .iter()
-            .filter(|(_, node)| node.degree == 0)
-            .map(|(id, _)| id.clone())
+            .map(|(id, list)| (id.as_str(), list.iter().map(String::as_str).collect()))
+            .collect();
+
+        self.clusters_detailed()
+            .into_iter()
+            .map(|detail| {
+                let mut triangle_count = 0;
+                for u in &detail.nodes {
+                    // Count each triangle once, from its smallest node ID
+                    let mut higher: Vec<&str> = neighbors
+                        .get(u.as_str())
+                        .into_iter()
+                        .flatten()
+                        .copied()
+                        .filter(|v| *v > u.as_str())
+                        .collect();
+                    higher.sort_unstable();
+                    for (i, v) in higher.iter().enumerate() {
+                        if let Some(v_neighbors) = neighbors.get(v) {
+                            triangle_count += higher[i + 1..]
+                                .iter()
+                                .filter(|w| v_neighbors.contains(*w))
+                                .count();
+                        }
+                    }
+                }
+
+                ClusterStatistics {
+                    id: detail.id,
+                    size: detail.nodes.len(),
+                    edge_count: detail.edges.len(),
+                    triangle_count,
+                }
+            })
             .collect()
     }
 
-    /// Convert the network to JSON format for output
-    pub fn to_json(&self) -> NetworkJSON {
-        // Get all clusters
-        let all_clusters_map = self.retrieve_clusters(true);
-
-        // Get counts of connected and singleton nodes
-        let connected_nodes_count = self.nodes.values().filter(|node| node.degree > 0).count();
+    /// Summarize cluster sizes: count, mean, median and max size, dyads, and
+    /// singletons
+    ///
+    /// Size statistics are 0 when there are no clusters.
+    pub fn cluster_size_summary(&self) -> ClusterSizeSummary {
+        let sizes: Vec<f64> = self
+            .retrieve_clusters(false)
+            .values()
+            .map(|nodes| nodes.len() as f64)
+            .collect();
+        let description = describe_vector(&sizes);
+
+        ClusterSizeSummary {
+            cluster_count: sizes.len(),
+            mean_size: description.as_ref().map_or(0.0, |d| d.mean),
+            median_size: description.as_ref().map_or(0.0, |d| d.median),
+            max_size: description.as_ref().map_or(0, |d| d.max as usize),
+            dyads: sizes.iter().filter(|&&size| size == 2.0).count(),
+            singletons: self.extract_singleton_nodes().len(),
+        }
+    }
 
-        let singleton_count = self.nodes.len() - connected_nodes_count;
+    /// Get the node pairs forming clusters of exactly two nodes (dyads)
+    ///
+    /// Each pair is normalized like an edge key, and pairs are sorted.
+    pub fn dyads(&self) -> Vec<(String, String)> {
+        let mut dyads: Vec<(String, String)> = self
+            .retrieve_clusters(false)
+            .into_values()
+            .filter(|nodes| nodes.len() == 2)
+            .map(|nodes| self.normalized_key(&nodes[0], &nodes[1]))
+            .collect();
+        dyads.sort();
+        dyads
+    }
 
-        // Identify real clusters (with 2+ connected nodes)
-        let mut connected_clusters: HashMap<usize, Vec<String>> = HashMap::new();
+    /// Name clusters using the configured naming scheme
+    ///
+    /// Returns a map of cluster ID -> name for clusters of 2+ connected nodes,
+    /// or an empty map when no naming scheme is set.
+    pub fn cluster_names(&self) -> HashMap<usize, String> {
+        let naming = match &self.cluster_naming {
+            Some(naming) => naming,
+            None => return HashMap::new(),
+        };
 
-        // Track which clusters have connected nodes
-        let mut real_cluster_ids = HashSet::new();
+        // Rank clusters by size (largest first), then by output ID
+        let mut clusters: Vec<(usize, Vec<String>)> = self
+            .retrieve_clusters(false)
+            .into_iter()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(id, mut members)| {
+                members.sort();
+                (id, members)
+            })
+            .collect();
+        clusters.sort_by(|(id_a, members_a), (id_b, members_b)| {
+            members_b.len().cmp(&members_a.len()).then_with(|| {
+                self.output_cluster_id(Some(*id_a))
+                    .cmp(&self.output_cluster_id(Some(*id_b)))
+            })
+        });
 
-        for (&cluster_id, nodes) in &all_clusters_map {
-            // Count nodes with degree > 0
-            let connected_node_ids: Vec<String> = nodes
-                .iter()
-                .filter(|node_id| {
-                    // Need to dereference the node_id
-                    let key: &String = node_id;
-                    if let Some(node) = self.nodes.get(key) {
-                        node.degree > 0
-                    } else {
-                        false
+        clusters
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (id, members))| {
+                let name = match naming {
+                    ClusterNaming::Prefix(prefix) => format!("{}{:03}", prefix, rank + 1),
+                    ClusterNaming::Callback(callback) => {
+                        callback(self.output_cluster_id(Some(id)), &members)
                     }
-                })
-                .cloned()
-                .collect();
+                };
+                (id, name)
+            })
+            .collect()
+    }
 
-            // If we have 2+ connected nodes, this is a real cluster
-            if connected_node_ids.len() > 1 {
-                real_cluster_ids.insert(cluster_id);
-                connected_clusters.insert(cluster_id, connected_node_ids);
+    /// Count nodes per calendar bin of their earliest date
+    ///
+    /// Bins are ISO weeks ("2020-W03") or months ("2020-01"); nodes without
+    /// any date are skipped. Labels sort chronologically.
+    pub fn date_histogram(&self, bucket: DateBucket) -> BTreeMap<String, usize> {
+        let mut histogram = BTreeMap::new();
+        for node in self.nodes.values() {
+            if let Some(date) = node.get_earliest_date() {
+                *histogram.entry(bucket.label(&date)).or_insert(0) += 1;
             }
         }
+        histogram
+    }
 
-        // Count real clusters with 2+ connected nodes
-        // We use the connected_clusters.len() instead
+    /// Find clusters that gained members recently
+    ///
+    /// Returns the IDs (sorted) of clusters of connected nodes containing at
+    /// least one node whose earliest date is on or after `since`.
+    pub fn recent_growth_clusters(&self, since: DateTime<Utc>) -> Vec<usize> {
+        let mut cluster_ids: Vec<usize> = self
+            .retrieve_clusters(false)
+            .into_iter()
+            .filter(|(_, members)| {
+                members.iter().any(|id| {
+                    self.nodes
+                        .get(id)
+                        .and_then(|node| node.get_earliest_date())
+                        .is_some_and(|date| date >= since)
+                })
+            })
+            .map(|(cluster_id, _)| cluster_id)
+            .collect();
+        cluster_ids.sort_unstable();
+        cluster_ids
+    }
 
-        // Get counts
-        let edge_count = self.edges.iter().filter(|edge| edge.visible).count();
-        let node_count = self.nodes.len();
-        let connected_node_count = connected_nodes_count; // Nodes with connections
-        let cluster_count = connected_clusters.len(); // Only use connected clusters with 2+ nodes
+    /// List clusters by their most recent sampling date, most recent first
+    ///
+    /// Pairs each cluster of connected nodes (by internal cluster ID) with the
+    /// latest date among its members. Clusters without dated members have no
+    /// date and sort last; ties are broken by cluster ID.
+    pub fn clusters_by_recency(&self) -> Vec<(usize, Option<DateTime<Utc>>)> {
+        let mut clusters: Vec<(usize, Option<DateTime<Utc>>)> = self
+            .retrieve_clusters(false)
+            .into_iter()
+            .map(|(cluster_id, members)| {
+                let latest = members
+                    .iter()
+                    .filter_map(|id| self.nodes.get(id))
+                    .filter_map(|node| node.get_most_recent_date())
+                    .max();
+                (cluster_id, latest)
+            })
+            .collect();
+        clusters.sort_by_key(|&(cluster_id, latest)| (std::cmp::Reverse(latest), cluster_id));
+        clusters
+    }
 
-        // Create cluster sizes
-        let mut cluster_sizes: Vec<usize> = connected_clusters
-            .values()
-            .map(|nodes| nodes.len())
+    /// Count links formed in a time window
+    ///
+    /// A visible, supported edge is counted when the later of its two endpoint
+    /// sample dates falls within `[start, end]`. Edges with an undated
+    /// endpoint are not counted, since when they formed is unknown.
+    pub fn links_added_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> usize {
+        self.edges
+            .iter()
+            .filter(|e| e.visible && !e.is_unsupported)
+            .filter_map(|e| Some(e.source_date?.max(e.target_date?)))
+            .filter(|formed| (start..=end).contains(formed))
+            .count()
+    }
+
+    /// Suggest a distance threshold at the given percentile (0-100) of edge distances
+    ///
+    /// The network should be loaded at a loose threshold so the distance
+    /// distribution is representative. Returns 0.0 if there are no edges.
+    pub fn suggest_threshold(&self, percentile_value: f64) -> f64 {
+        let distances: Vec<f64> = self
+            .edges
+            .iter()
+            .filter(|e| e.visible && !e.is_unsupported)
+            .map(|e| e.distance)
             .collect();
-        cluster_sizes.sort_unstable();
 
-        // Create vectors of nodes for output
-        let mut node_ids: Vec<String> = Vec::with_capacity(node_count);
-        let mut node_clusters: Vec<usize> = Vec::with_capacity(node_count);
-        let mut node_attributes: Vec<serde_json::Value> = Vec::with_capacity(node_count);
+        percentile(&distances, percentile_value).unwrap_or(0.0)
+    }
 
-        // For consistent ordering, get sorted node IDs
-        let mut sorted_node_ids: Vec<&String> = self.nodes.keys().collect();
-        sorted_node_ids.sort();
+    /// Count the loaded edges that pass a (typically looser) threshold
+    ///
+    /// Hidden edges are counted too, so this previews the edge count after
+    /// re-thresholding without re-clustering. Unsupported edges are excluded,
+    /// and edges beyond the threshold used when loading are never loaded, so
+    /// the network should be loaded at the loosest threshold of interest.
+    pub fn edges_below(&self, threshold: f64) -> usize {
+        self.edges
+            .iter()
+            .filter(|e| !e.is_unsupported && self.threshold_mode.passes(e.distance, threshold))
+            .count()
+    }
 
-        // Create node index map and populate node vectors
-        let mut node_id_to_index: HashMap<String, usize> = HashMap::with_capacity(node_count);
+    /// Compute clustering at a sweep of distance thresholds
+    ///
+    /// For each threshold, returns (threshold, clustered_node_count, cluster_count),
+    /// where clustered nodes have at least one edge at that threshold. The network
+    /// should be loaded at the loosest threshold of interest.
+    pub fn threshold_sweep(&self, thresholds: &[f64]) -> Vec<(f64, usize, usize)> {
+        let mut node_ids: Vec<&String> = self.nodes.keys().collect();
+        node_ids.sort();
+        let node_index: HashMap<&str, usize> = node_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
 
-        for (idx, &node_id) in sorted_node_ids.iter().enumerate() {
-            node_id_to_index.insert(node_id.clone(), idx);
-            node_ids.push(node_id.clone());
+        thresholds
+            .iter()
+            .map(|&threshold| {
+                // Union-find over the edges that pass this threshold
+                let mut parent: Vec<usize> = (0..node_ids.len()).collect();
+                let mut connected = vec![false; node_ids.len()];
+
+                for edge in &self.edges {
+                    if edge.is_unsupported || !self.threshold_mode.passes(edge.distance, threshold)
+                    {
+                        continue;
+                    }
+                    let (a, b) = match (
+                        node_index.get(edge.source_id.as_str()),
+                        node_index.get(edge.target_id.as_str()),
+                    ) {
+                        (Some(&a), Some(&b)) => (a, b),
+                        _ => continue,
+                    };
+
+                    connected[a] = true;
+                    connected[b] = true;
+                    let (root_a, root_b) = (find_root(&mut parent, a), find_root(&mut parent, b));
+                    if root_a != root_b {
+                        parent[root_a] = root_b;
+                    }
+                }
 
-            let node = &self.nodes[node_id];
+                let clustered_node_count = connected.iter().filter(|&&c| c).count();
+                let cluster_count = (0..node_ids.len())
+                    .filter(|&idx| connected[idx] && find_root(&mut parent, idx) == idx)
+                    .count();
 
-            // Use 1-indexed cluster IDs as per original format
-            let cluster_id = node.cluster_id.map(|id| id + 1).unwrap_or(0);
-            node_clusters.push(cluster_id);
+                (threshold, clustered_node_count, cluster_count)
+            })
+            .collect()
+    }
 
-            // For compatibility, just provide minimal attributes
-            node_attributes.push(serde_json::json!({}));
-        }
+    /// Count how many clusters at threshold `from` would merge when the
+    /// threshold is loosened to `to`
+    ///
+    /// Each merge of two (or more) separate clusters into one counts once per
+    /// cluster absorbed, so two dyads joining is one merge event; nodes that
+    /// were singletons at `from` joining a cluster don't count. Unsupported
+    /// edges are excluded, and the network should be loaded at the loosest
+    /// threshold of interest.
+    pub fn merge_events(&self, from: f64, to: f64) -> usize {
+        let mut node_ids: Vec<&String> = self.nodes.keys().collect();
+        node_ids.sort();
+        let node_index: HashMap<&str, usize> = node_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
+        let edge_endpoints = |edge: &Edge| match (
+            node_index.get(edge.source_id.as_str()),
+            node_index.get(edge.target_id.as_str()),
+        ) {
+            (Some(&a), Some(&b)) => Some((a, b)),
+            _ => None,
+        };
 
-        // Create edge vectors
-        let mut edge_sequences: Vec<Vec<String>> = Vec::with_capacity(edge_count);
-        let mut edge_sources: Vec<usize> = Vec::with_capacity(edge_count);
-        let mut edge_targets: Vec<usize> = Vec::with_capacity(edge_count);
-        let mut edge_lengths: Vec<f64> = Vec::with_capacity(edge_count);
+        // Union-find over the edges that pass the original threshold
+        let mut parent: Vec<usize> = (0..node_ids.len()).collect();
+        let mut clustered = vec![false; node_ids.len()];
+        for edge in &self.edges {
+            if edge.is_unsupported || !self.threshold_mode.passes(edge.distance, from) {
+                continue;
+            }
+            if let Some((a, b)) = edge_endpoints(edge) {
+                let (root_a, root_b) = (find_root(&mut parent, a), find_root(&mut parent, b));
+                if root_a != root_b {
+                    parent[root_a] = root_b;
+                }
+                clustered[root_b] = true;
+            }
+        }
+        for idx in 0..node_ids.len() {
+            if clustered[idx] {
+                let root = find_root(&mut parent, idx);
+                clustered[root] = true;
+            }
+        }
 
-        for edge in self.edges.iter().filter(|edge| edge.visible) {
-            // Skip edges for nodes that don't exist in the index
-            if !node_id_to_index.contains_key(&edge.source_id)
-                || !node_id_to_index.contains_key(&edge.target_id)
+        // Add the edges that only pass the looser threshold, counting every
+        // union that joins two components which were both clusters
+        let mut merges = 0;
+        for edge in &self.edges {
+            if edge.is_unsupported
+                || !self.threshold_mode.passes(edge.distance, to)
+                || self.threshold_mode.passes(edge.distance, from)
             {
                 continue;
             }
-
-            let source_idx = node_id_to_index[&edge.source_id];
-            let target_idx = node_id_to_index[&edge.target_id];
-
-            edge_sequences.push(vec![edge.source_id.clone(), edge.target_id.clone()]);
-            edge_sources.push(source_idx);
-            edge_targets.push(target_idx);
-            edge_lengths.push(edge.distance);
+            if let Some((a, b)) = edge_endpoints(edge) {
+                let (root_a, root_b) = (find_root(&mut parent, a), find_root(&mut parent, b));
+                if root_a == root_b {
+                    continue;
+                }
+                if clustered[root_a] && clustered[root_b] {
+                    merges += 1;
+                }
+                parent[root_a] = root_b;
+                clustered[root_b] |= clustered[root_a];
+            }
         }
 
-        // Values for directed edges
-        let directed_keys = HashMap::from([("0".to_string(), false)]);
-        let directed_values = vec![0; edge_sources.len()];
+        merges
+    }
 
-        // Values for attributes
-        let attribute_keys = HashMap::from([("0".to_string(), vec!["BULK".to_string()])]);
-        let attribute_values = vec![0; edge_sources.len()];
+    /// Compute the k-core: nodes remaining after iteratively removing nodes
+    /// with fewer than `k` neighbors in the visible graph
+    pub fn k_core(&self, k: usize) -> HashSet<String> {
+        let mut degrees: HashMap<&str, usize> = self
+            .adjacency
+            .iter()
+            .map(|(id, neighbors)| (id.as_str(), neighbors.len()))
+            .collect();
 
-        // Values for support
-        let support_keys = HashMap::from([("0".to_string(), 0.0)]);
-        let support_values = vec![0; edge_sources.len()];
+        let mut removed: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<&str> = degrees
+            .iter()
+            .filter(|(_, &degree)| degree < k)
+            .map(|(&id, _)| id)
+            .collect();
 
-        // Calculate degree distribution
-        let max_degree = self
+        while let Some(node_id) = queue.pop_front() {
+            if !removed.insert(node_id) {
+                continue;
+            }
+
+            for neighbor_id in self.adjacency.get(node_id).into_iter().flatten() {
+                if removed.contains(neighbor_id.as_str()) {
+                    continue;
+                }
+                if let Some(degree) = degrees.get_mut(neighbor_id.as_str()) {
+                    *degree = degree.saturating_sub(1);
+                    if *degree < k {
+                        queue.push_back(neighbor_id);
+                    }
+                }
+            }
+        }
+
+        self.nodes
+            .keys()
+            .filter(|id| degrees.contains_key(id.as_str()) && !removed.contains(id.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Build a degree-preserving randomization of the network (null model)
+    ///
+    /// Performs up to `swaps` successful double-edge swaps — (a, b), (c, d)
+    /// become (a, d), (c, b) — over the visible edges, rejecting swaps that
+    /// would create self-loops or multi-edges. Every node keeps its degree and
+    /// each edge slot keeps its distance. The result has adjacency and clusters
+    /// computed. The same seed always gives the same network.
+    pub fn degree_preserving_shuffle(&self, seed: u64, swaps: usize) -> TransmissionNetwork {
+        let mut edges: Vec<(String, String, f64)> = self
+            .edges
+            .iter()
+            .filter(|e| e.visible && !e.is_unsupported)
+            .map(|e| (e.source_id.clone(), e.target_id.clone(), e.distance))
+            .collect();
+        let mut edge_keys: HashSet<(String, String)> = edges
+            .iter()
+            .map(|(a, b, _)| self.normalized_key(a, b))
+            .collect();
+
+        let mut rng = SplitMix64::new(seed);
+        let max_attempts = swaps.saturating_mul(10);
+        let mut completed = 0;
+        let mut attempts = 0;
+        while edges.len() >= 2 && completed < swaps && attempts < max_attempts {
+            attempts += 1;
+
+            let i = rng.next_index(edges.len());
+            let j = rng.next_index(edges.len());
+            if i == j {
+                continue;
+            }
+
+            // Randomly pick which endpoints to exchange
+            let (a, b) = (edges[i].0.clone(), edges[i].1.clone());
+            let (c, d) = if rng.next_index(2) == 0 {
+                (edges[j].0.clone(), edges[j].1.clone())
+            } else {
+                (edges[j].1.clone(), edges[j].0.clone())
+            };
+
+            // (a, b), (c, d) -> (a, d), (c, b)
+            if a == d || c == b {
+                continue;
+            }
+            let new_key_1 = self.normalized_key(&a, &d);
+            let new_key_2 = self.normalized_key(&c, &b);
+            if new_key_1 == new_key_2
+                || edge_keys.contains(&new_key_1)
+                || edge_keys.contains(&new_key_2)
+            {
+                continue;
+            }
+
+            edge_keys.remove(&self.normalized_key(&a, &b));
+            edge_keys.remove(&self.normalized_key(&c, &d));
+            edge_keys.insert(new_key_1);
+            edge_keys.insert(new_key_2);
+            edges[i] = (a, d, edges[i].2);
+            edges[j] = (c, b, edges[j].2);
+            completed += 1;
+        }
+
+        // Rebuild a network with the same nodes and the rewired edges
+        let mut shuffled = TransmissionNetwork::new();
+        shuffled.id_ordering = self.id_ordering;
+        shuffled.metadata = self.metadata.clone();
+        shuffled.node_order = self.node_order.clone();
+        for (id, node) in &self.nodes {
+            let mut node = node.clone();
+            node.degree = 0;
+            node.cluster_id = None;
+            shuffled.nodes.insert(id.clone(), node);
+            shuffled.adjacency.insert(id.clone(), Vec::new());
+        }
+
+        for (source_id, target_id, distance) in edges {
+            let source_date = self.nodes[&source_id].get_most_recent_date();
+            let target_date = self.nodes[&target_id].get_most_recent_date();
+            if let Ok(edge) = Edge::new_with_ordering(
+                source_id,
+                target_id,
+                source_date,
+                target_date,
+                distance,
+                self.id_ordering,
+            ) {
+                let key = edge.get_key();
+                if let Some(node) = shuffled.nodes.get_mut(&key.0) {
+                    node.increment_degree();
+                }
+                if let Some(node) = shuffled.nodes.get_mut(&key.1) {
+                    node.increment_degree();
+                }
+                shuffled.edge_lookup.insert(key, shuffled.edges.len());
+                shuffled.edges.push(edge);
+            }
+        }
+
+        shuffled.update_stats();
+        shuffled.compute_adjacency();
+        shuffled.compute_clusters();
+        shuffled
+    }
+
+    /// Extract the neighborhood (ego network) of a node
+    ///
+    /// Returns the subgraph of all nodes within `radius` hops of `center` over
+    /// the visible adjacency, with the visible edges among them. The result is
+    /// empty if `center` is not in the network.
+    pub fn ego_network(&self, center: &str, radius: usize) -> TransmissionNetwork {
+        if !self.nodes.contains_key(center) {
+            return self.subnetwork(&[]);
+        }
+
+        // Breadth-first search bounded by the radius
+        let mut hops: HashMap<&str, usize> = HashMap::from([(center, 0)]);
+        let mut queue: VecDeque<&str> = VecDeque::from([center]);
+        while let Some(current) = queue.pop_front() {
+            let depth = hops[current];
+            if depth == radius {
+                continue;
+            }
+            for neighbor in self.adjacency.get(current).into_iter().flatten() {
+                if !hops.contains_key(neighbor.as_str()) {
+                    hops.insert(neighbor, depth + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        let members: Vec<String> = hops.into_keys().map(str::to_string).collect();
+        self.subnetwork(&members)
+    }
+
+    /// Extract one cluster (by internal cluster ID) as its own network
+    ///
+    /// Contains the cluster's nodes and the visible edges among them, with
+    /// adjacency recomputed. Nodes keep `cluster_id`, so the extracted network
+    /// reports the same cluster ID as this one.
+    pub fn extract_cluster(&self, cluster_id: usize) -> TransmissionNetwork {
+        let members: Vec<String> = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.cluster_id == Some(cluster_id))
+            .map(|(id, _)| id.clone())
+            .collect();
+        let mut cluster = self.subnetwork(&members);
+        for node in cluster.nodes.values_mut() {
+            node.cluster_id = Some(cluster_id);
+        }
+        cluster
+    }
+
+    /// Build the subgraph induced by a set of node IDs
+    ///
+    /// Unknown IDs are ignored. Only visible, supported edges between members
+    /// are kept; degrees, adjacency and clusters are recomputed.
+    pub fn subnetwork(&self, node_ids: &[String]) -> TransmissionNetwork {
+        let members: HashSet<&str> = node_ids
+            .iter()
+            .map(String::as_str)
+            .filter(|id| self.nodes.contains_key(*id))
+            .collect();
+
+        let mut sub = TransmissionNetwork::new();
+        sub.id_ordering = self.id_ordering;
+        sub.threshold_mode = self.threshold_mode;
+        sub.metadata = self.metadata.clone();
+        sub.node_order = self
+            .node_order
+            .iter()
+            .filter(|id| members.contains(id.as_str()))
+            .cloned()
+            .collect();
+        for id in &members {
+            let mut node = self.nodes[*id].clone();
+            node.degree = 0;
+            node.cluster_id = None;
+            sub.nodes.insert(id.to_string(), node);
+        }
+
+        for edge in &self.edges {
+            if !edge.visible
+                || edge.is_unsupported
+                || !members.contains(edge.source_id.as_str())
+                || !members.contains(edge.target_id.as_str())
+            {
+                continue;
+            }
+            let key = edge.get_key();
+            if let Some(node) = sub.nodes.get_mut(&key.0) {
+                node.increment_degree();
+            }
+            if let Some(node) = sub.nodes.get_mut(&key.1) {
+                node.increment_degree();
+            }
+            sub.edge_lookup.insert(key, sub.edges.len());
+            sub.edges.push(edge.clone());
+        }
+
+        sub.update_stats();
+        sub.compute_adjacency();
+        sub.compute_clusters();
+        sub
+    }
+
+    /// Compute edge betweenness over the visible graph (Brandes' algorithm)
+    ///
+    /// Returns the number of shortest paths passing through each edge, keyed
+    /// by its normalized (source, target) pair. Shortest paths never leave a
+    /// cluster, so each cluster is effectively handled on its own.
+    pub fn edge_betweenness(&self) -> HashMap<(String, String), f64> {
+        brandes_edge_betweenness(&self.adjacency)
+            .into_iter()
+            .map(|((a, b), value)| (self.normalized_key(&a, &b), value))
+            .collect()
+    }
+
+    /// Split a cluster into communities using the Girvan–Newman method
+    ///
+    /// Repeatedly removes the edge with the highest betweenness until the
+    /// cluster falls apart into `target_communities` components (or runs out
+    /// of edges). Communities are returned largest first, each sorted by ID.
+    pub fn girvan_newman(&self, cluster_id: usize, target_communities: usize) -> Vec<Vec<String>> {
+        // Work on a copy of the cluster's adjacency
+        let mut adjacency: HashMap<String, Vec<String>> = self
             .nodes
+            .iter()
+            .filter(|(_, node)| node.cluster_id == Some(cluster_id))
+            .map(|(id, _)| {
+                (
+                    id.clone(),
+                    self.adjacency.get(id).cloned().unwrap_or_default(),
+                )
+            })
+            .collect();
+
+        loop {
+            let communities = connected_components(&adjacency);
+            let has_edges = adjacency.values().any(|neighbors| !neighbors.is_empty());
+            if communities.len() >= target_communities || !has_edges {
+                return communities;
+            }
+
+            // Remove the edge with the highest betweenness (ties broken by key)
+            let betweenness = brandes_edge_betweenness(&adjacency);
+            let ((a, b), _) = betweenness
+                .into_iter()
+                .max_by(|(key_a, value_a), (key_b, value_b)| {
+                    value_a
+                        .partial_cmp(value_b)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then_with(|| key_b.cmp(key_a))
+                })
+                .expect("graph with edges has betweenness values");
+
+            if let Some(neighbors) = adjacency.get_mut(&a) {
+                neighbors.retain(|id| id != &b);
+            }
+            if let Some(neighbors) = adjacency.get_mut(&b) {
+                neighbors.retain(|id| id != &a);
+            }
+        }
+    }
+
+    /// Newman's modularity Q of the current cluster assignment
+    ///
+    /// Computed over the visible adjacency as the sum, over clusters, of the
+    /// fraction of edges inside the cluster minus the fraction expected from
+    /// the cluster's total degree. Returns 0.0 for a network without edges.
+    pub fn modularity(&self) -> f64 {
+        let degree_sum: usize = self
+            .adjacency
             .values()
-            .map(|node| node.degree)
-            .max()
-            .unwrap_or(0);
+            .map(|neighbors| neighbors.len())
+            .sum();
+        if degree_sum == 0 {
+            return 0.0;
+        }
+        let total = degree_sum as f64;
+
+        // Per cluster: endpoints of internal edges, and total degree
+        let mut internal: HashMap<usize, usize> = HashMap::new();
+        let mut degrees: HashMap<usize, usize> = HashMap::new();
+        for (id, neighbors) in &self.adjacency {
+            let cluster = match self.nodes.get(id).and_then(|node| node.cluster_id) {
+                Some(cluster) => cluster,
+                None => continue,
+            };
+            *degrees.entry(cluster).or_insert(0) += neighbors.len();
+            *internal.entry(cluster).or_insert(0) += neighbors
+                .iter()
+                .filter(|neighbor| {
+                    self.nodes.get(*neighbor).and_then(|node| node.cluster_id) == Some(cluster)
+                })
+                .count();
+        }
 
-        let mut degree_distribution = vec![0; max_degree + 1];
-        for node in self.nodes.values() {
-            degree_distribution[node.degree] += 1;
+        degrees
+            .iter()
+            .map(|(cluster, &degree)| {
+                let inside = internal.get(cluster).copied().unwrap_or(0) as f64 / total;
+                let expected = degree as f64 / total;
+                inside - expected * expected
+            })
+            .sum()
+    }
+
+    /// Degree of every node counted over the visible, supported edges
+    fn visible_degrees(&self) -> HashMap<&String, usize> {
+        let mut degrees: HashMap<&String, usize> = self.nodes.keys().map(|id| (id, 0)).collect();
+        for edge in self.edges.iter().filter(|e| e.visible && !e.is_unsupported) {
+            for id in [&edge.source_id, &edge.target_id] {
+                if let Some(degree) = degrees.get_mut(id) {
+                    *degree += 1;
+                }
+            }
+        }
+        degrees
+    }
+
+    /// Normalize a node pair into an edge key using the configured ID ordering
+    fn normalized_key(&self, a: &str, b: &str) -> (String, String) {
+        if self.id_ordering.compare(a, b) == std::cmp::Ordering::Greater {
+            (b.to_string(), a.to_string())
+        } else {
+            (a.to_string(), b.to_string())
+        }
+    }
+
+    /// Find bridge edges (cut edges) whose removal would split a cluster
+    ///
+    /// Uses Tarjan's DFS-based algorithm over the adjacency list and returns
+    /// (source, target) pairs normalized with the configured ID ordering (as
+    /// in `edge_lookup`), sorted by ID.
+    pub fn bridge_edges(&self) -> Vec<(String, String)> {
+        let mut discovery: HashMap<&str, usize> = HashMap::new();
+        let mut low: HashMap<&str, usize> = HashMap::new();
+        let mut bridges = Vec::new();
+        let mut timer = 0;
+
+        let mut start_ids: Vec<&String> = self.adjacency.keys().collect();
+        start_ids.sort();
+
+        for start_id in start_ids {
+            if discovery.contains_key(start_id.as_str()) {
+                continue;
+            }
+
+            discovery.insert(start_id, timer);
+            low.insert(start_id, timer);
+            timer += 1;
+
+            // Iterative DFS: (node, parent, index of next neighbor to visit)
+            let mut stack: Vec<(&str, Option<&str>, usize)> = vec![(start_id, None, 0)];
+
+            while let Some(frame) = stack.last_mut() {
+                let (node_id, parent_id, next_idx) = *frame;
+                let neighbors = self
+                    .adjacency
+                    .get(node_id)
+                    .map(Vec::as_slice)
+                    .unwrap_or(&[]);
+
+                if next_idx < neighbors.len() {
+                    frame.2 += 1;
+                    let neighbor_id = neighbors[next_idx].as_str();
+
+                    if Some(neighbor_id) == parent_id {
+                        continue;
+                    }
+
+                    if let Some(&neighbor_discovery) = discovery.get(neighbor_id) {
+                        // Back edge
+                        let node_low = low[node_id].min(neighbor_discovery);
+                        low.insert(node_id, node_low);
+                    } else {
+                        // Tree edge
+                        discovery.insert(neighbor_id, timer);
+                        low.insert(neighbor_id, timer);
+                        timer += 1;
+                        stack.push((neighbor_id, Some(node_id), 0));
+                    }
+                } else {
+                    stack.pop();
+
+                    if let Some(parent_id) = parent_id {
+                        let parent_low = low[parent_id].min(low[node_id]);
+                        low.insert(parent_id, parent_low);
+
+                        if low[node_id] > discovery[parent_id] {
+                            bridges.push(self.normalized_key(parent_id, node_id));
+                        }
+                    }
+                }
+            }
+        }
+
+        bridges.sort();
+        bridges
+    }
+
+    /// Extract nodes that have no connections (singletons)
+    pub fn extract_singleton_nodes(&self) -> Vec<String> {
+        self.nodes
+            .iter()
+            .filter(|(_, node)| node.degree == 0)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Convert the network to JSON format for output
+    pub fn to_json(&self) -> NetworkJSON {
+        // Get all clusters
+        let all_clusters_map = self.retrieve_clusters(true);
+
+        // Degrees are counted from the visible edges throughout, so that edges
+        // hidden after parsing are not counted through stale node degrees
+        let visible_degrees = self.visible_degrees();
+
+        // Get counts of connected and singleton nodes
+        let connected_nodes_count = visible_degrees
+            .values()
+            .filter(|&&degree| degree > 0)
+            .count();
+
+        let singleton_count = self.nodes.len() - connected_nodes_count;
+
+        // Identify real clusters (with 2+ connected nodes)
+        let mut connected_clusters: HashMap<usize, Vec<String>> = HashMap::new();
+
+        // Track which clusters have connected nodes
+        let mut real_cluster_ids = HashSet::new();
+
+        for (&cluster_id, nodes) in &all_clusters_map {
+            // Count nodes with degree > 0
+            let connected_node_ids: Vec<String> = nodes
+                .iter()
+                .filter(|node_id| {
+                    visible_degrees
+                        .get(node_id)
+                        .is_some_and(|&degree| degree > 0)
+                })
+                .cloned()
+                .collect();
+
+            // If we have 2+ connected nodes, this is a real cluster
+            if connected_node_ids.len() > 1 {
+                real_cluster_ids.insert(cluster_id);
+                connected_clusters.insert(cluster_id, connected_node_ids);
+            }
+        }
+
+        // Count real clusters with 2+ connected nodes
+        // We use the connected_clusters.len() instead
+
+        // Get counts
+        let edge_count = self.get_edge_count();
+        let node_count = self.nodes.len();
+        let connected_node_count = connected_nodes_count; // Nodes with connections
+        let sequences_used = match self.sequence_count_mode {
+            SequenceCountMode::ConnectedNodes => connected_node_count,
+            SequenceCountMode::DistinctSamples => self
+                .nodes
+                .iter()
+                .filter(|(id, _)| visible_degrees[id] > 0)
+                .map(|(_, node)| node.dates.len().max(1))
+                .sum(),
+        };
+        let cluster_count = connected_clusters.len(); // Only use connected clusters with 2+ nodes
+
+        // Count visible intra-cluster edges per cluster
+        let mut edges_per_cluster: HashMap<usize, usize> = HashMap::new();
+        for edge in self.edges.iter().filter(|e| e.visible && !e.is_unsupported) {
+            let source_cluster = self.nodes.get(&edge.source_id).and_then(|n| n.cluster_id);
+            let target_cluster = self.nodes.get(&edge.target_id).and_then(|n| n.cluster_id);
+            if let (Some(source_cluster), Some(target_cluster)) = (source_cluster, target_cluster) {
+                if source_cluster == target_cluster {
+                    *edges_per_cluster.entry(source_cluster).or_insert(0) += 1;
+                }
+            }
+        }
+
+        // Create cluster sizes, with edge counts aligned to them
+        let mut cluster_size_edges: Vec<(usize, usize)> = connected_clusters
+            .iter()
+            .map(|(id, nodes)| (nodes.len(), edges_per_cluster.get(id).copied().unwrap_or(0)))
+            .collect();
+        cluster_size_edges.sort_unstable();
+        let (cluster_sizes, cluster_edge_counts): (Vec<usize>, Vec<usize>) =
+            cluster_size_edges.into_iter().unzip();
+
+        // Optionally pair sizes with cluster IDs, largest first
+        let cluster_sizes_with_ids = if self.cluster_sizes_with_ids {
+            let mut sizes: Vec<ClusterSize> = connected_clusters
+                .iter()
+                .map(|(&id, nodes)| ClusterSize {
+                    id: self.output_cluster_id(Some(id)),
+                    size: nodes.len(),
+                })
+                .collect();
+            sizes.sort_by(|a, b| b.size.cmp(&a.size).then(a.id.cmp(&b.id)));
+            Some(sizes)
+        } else {
+            None
+        };
+
+        // Optionally name clusters, keyed by output cluster ID
+        let cluster_names = self.cluster_naming.as_ref().map(|_| {
+            self.cluster_names()
+                .into_iter()
+                .map(|(id, name)| (self.output_cluster_id(Some(id)), name))
+                .collect::<BTreeMap<i64, String>>()
+        });
+
+        // Create vectors of nodes for output
+        let mut node_ids: Vec<String> = Vec::with_capacity(node_count);
+        let mut node_clusters: Vec<i64> = Vec::with_capacity(node_count);
+        let mut node_attributes: Vec<serde_json::Value> = Vec::with_capacity(node_count);
+
+        // For consistent ordering, get sorted (or first-seen) node IDs
+        let sorted_node_ids = self.ordered_node_ids();
+
+        // Create node index map and populate node vectors
+        let mut node_id_to_index: HashMap<String, usize> = HashMap::with_capacity(node_count);
+
+        for (idx, &node_id) in sorted_node_ids.iter().enumerate() {
+            node_id_to_index.insert(node_id.clone(), idx);
+            node_ids.push(node_id.clone());
+
+            let node = &self.nodes[node_id];
+
+            node_clusters.push(self.output_node_cluster_id(node, visible_degrees[node_id]));
+
+            // For compatibility, just provide minimal attributes
+            node_attributes.push(serde_json::json!({}));
         }
 
+        // Create edge vectors
+        let mut edge_sequences: Vec<Vec<String>> = Vec::with_capacity(edge_count);
+        let mut edge_sources: Vec<usize> = Vec::with_capacity(edge_count);
+        let mut edge_targets: Vec<usize> = Vec::with_capacity(edge_count);
+        let mut edge_lengths: Vec<f64> = Vec::with_capacity(edge_count);
+        let mut edge_removed: Vec<usize> = Vec::with_capacity(edge_count);
+        let mut removed_reasons: HashMap<String, String> = HashMap::new();
+        let mut edge_directions: Vec<i8> = Vec::new();
+        let mut edge_swapped: Vec<bool> = Vec::new();
+
+        for edge in self
+            .edges
+            .iter()
+            .filter(|edge| edge.visible || self.include_hidden_edges)
+        {
+            // Skip edges for nodes that don't exist in the index
+            if !node_id_to_index.contains_key(&edge.source_id)
+                || !node_id_to_index.contains_key(&edge.target_id)
+            {
+                continue;
+            }
+
+            let source_idx = node_id_to_index[&edge.source_id];
+            let target_idx = node_id_to_index[&edge.target_id];
+
+            edge_sequences.push(vec![edge.source_id.clone(), edge.target_id.clone()]);
+            edge_sources.push(source_idx);
+            edge_targets.push(target_idx);
+            edge_lengths.push(edge.distance);
+            if self.include_edge_direction {
+                edge_directions.push(edge.direction());
+                edge_swapped.push(edge.orientation_swapped);
+            }
+
+            match edge.removal_reason() {
+                Some(reason) => {
+                    removed_reasons.insert(edge_removed.len().to_string(), reason.to_string());
+                    edge_removed.push(1);
+                }
+                None => edge_removed.push(0),
+            }
+        }
+
+        // Optional similarity weights derived from distances
+        let edge_weights = if self.include_edge_weights {
+            Some(
+                edge_lengths
+                    .iter()
+                    .map(|distance| (1.0 - distance).clamp(0.0, 1.0))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        // Optionally round the emitted distances
+        if let Some(figures) = self.distance_significant_figures {
+            for length in edge_lengths.iter_mut() {
+                *length = round_to_significant_figures(*length, figures);
+            }
+        }
+
+        // Values for directed edges
+        let directed_keys = HashMap::from([("0".to_string(), false)]);
+        let directed_values = vec![0; edge_sources.len()];
+
+        // Values for removed (unsupported or hidden) edges
+        let removed_keys = HashMap::from([("0".to_string(), false), ("1".to_string(), true)]);
+
+        // Values for attributes
+        let attribute_keys = HashMap::from([("0".to_string(), vec!["BULK".to_string()])]);
+        let attribute_values = vec![0; edge_sources.len()];
+
+        // Values for support
+        let support_keys = HashMap::from([("0".to_string(), 0.0)]);
+        let support_values = vec![0; edge_sources.len()];
+
+        // Calculate degree distribution from the visible edges
+        let max_degree = visible_degrees.values().copied().max().unwrap_or(0);
+
+        // Optionally cap the distribution, with a final bin for degrees >= the cap
+        let last_bin = match self.max_reported_degree {
+            Some(cap) => max_degree.min(cap),
+            None => max_degree,
+        };
+        let mut degree_distribution = vec![0; last_bin + 1];
+        for degree in visible_degrees.values() {
+            degree_distribution[(*degree).min(last_bin)] += 1;
+        }
+
+        // Classify why edges could (not) be directed by sampling date
+        let directed_edges = self.directed_edge_summary();
+
         // Create HIV stages mapping
         let mut hiv_stages = HashMap::new();
         hiv_stages.insert("Unknown".to_string(), node_count);
@@ -679,26 +2769,55 @@ impl TransmissionNetwork {
         // Current timestamp
         let current_time = Utc::now().to_rfc3339();
 
+        let provenance = if self.include_provenance {
+            Some(Provenance {
+                input_file: self.input_file.clone(),
+                input_sha256: self.input_sha256.clone(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                threshold,
+                format: self
+                    .metadata
+                    .get("format")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                created: current_time.clone(),
+                input_rows: self
+                    .metadata
+                    .get("input_rows")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0) as usize,
+                nodes: node_count,
+                edges: edge_count,
+            })
+        } else {
+            None
+        };
+
         // Create output format
         NetworkJSON {
             trace_results: TraceResults {
                 network_summary: NetworkSummary {
                     Edges: edge_count,
                     Nodes: node_count,
-                    sequences_used: connected_node_count, // Only count nodes used in connections
+                    sequences_used, // Only count nodes/samples used in connections
                     Clusters: cluster_count,
                     Singletons: singleton_count,
+                    dyads: connected_clusters
+                        .values()
+                        .filter(|nodes| nodes.len() == 2)
+                        .count(),
                 },
                 multiple_sequences: MultipleSequences {
                     subjects_with: 0,
                     followup_days: None,
                 },
                 cluster_sizes,
+                cluster_edge_counts,
+                cluster_sizes_with_ids,
+                cluster_names,
+                provenance,
                 hiv_stages,
-                directed_edges: DirectedEdges {
-                    Count: 0,
-                    reasons: HashMap::from([("Missing dates".to_string(), edge_count)]),
-                },
+                directed_edges,
                 degrees: Degrees {
                     Distribution: degree_distribution,
                     Model: "None".to_string(),
@@ -709,11 +2828,15 @@ impl TransmissionNetwork {
                 },
                 settings: Settings {
                     threshold,
-                    edge_filtering: None,
+                    edge_filtering: self
+                        .min_threshold
+                        .map(|min| format!("distance in [{}, {}]", min, threshold)),
                     contaminants: None,
                     singletons: true,
                     compact_json: true,
                     created: current_time,
+                    sequences_used: self.sequence_count_mode.label().to_string(),
+                    collapsed_singletons: self.collapse_singletons,
                 },
                 nodes: NodesOutput {
                     cluster: node_clusters,
@@ -722,19 +2845,23 @@ impl TransmissionNetwork {
                 },
                 edges: EdgesOutput {
                     directed: DirectedValues {
-                        keys: directed_keys.clone(),
-                        values: directed_values.clone(),
+                        keys: directed_keys,
+                        values: directed_values,
                     },
                     sequences: edge_sequences,
                     target: edge_targets,
                     length: edge_lengths,
+                    weight: edge_weights,
+                    direction: self.include_edge_direction.then_some(edge_directions),
+                    orientation_swapped: self.include_edge_direction.then_some(edge_swapped),
                     attributes: AttributeValues {
                         keys: attribute_keys,
                         values: attribute_values,
                     },
-                    removed: DirectedValues {
-                        keys: directed_keys,
-                        values: directed_values,
+                    removed: RemovedValues {
+                        keys: removed_keys,
+                        values: edge_removed,
+                        reasons: removed_reasons,
                     },
                     support: SupportValues {
                         keys: support_keys,
@@ -747,12 +2874,86 @@ impl TransmissionNetwork {
         }
     }
 
+    /// Summarize which edges can be directed by sampling date
+    ///
+    /// Edges with two distinct dates are counted as directed; the rest are
+    /// bucketed by why their direction is unresolved.
+    fn directed_edge_summary(&self) -> DirectedEdges {
+        let mut count = 0;
+        let mut both_missing = 0;
+        let mut one_missing = 0;
+        let mut equal_dates = 0;
+
+        for edge in self.edges.iter().filter(|e| e.visible && !e.is_unsupported) {
+            match (edge.source_date, edge.target_date) {
+                (None, None) => both_missing += 1,
+                (Some(_), None) | (None, Some(_)) => one_missing += 1,
+                (Some(a), Some(b)) if a == b => equal_dates += 1,
+                _ => count += 1,
+            }
+        }
+
+        DirectedEdges {
+            Count: count,
+            reasons: HashMap::from([
+                ("Both dates missing".to_string(), both_missing),
+                ("One date missing".to_string(), one_missing),
+                ("Equal dates".to_string(), equal_dates),
+            ]),
+        }
+    }
+
+    /// Node IDs in output order: sorted by ID, or first-seen order if
+    /// `preserve_node_order` is set (nodes not tracked in `node_order` go last)
+    fn ordered_node_ids(&self) -> Vec<&String> {
+        let mut sorted_node_ids: Vec<&String> = self.nodes.keys().collect();
+        sorted_node_ids.sort();
+
+        if !self.preserve_node_order {
+            return sorted_node_ids;
+        }
+
+        let mut seen = HashSet::new();
+        let mut ordered: Vec<&String> = self
+            .node_order
+            .iter()
+            .filter(|id| self.nodes.contains_key(*id) && seen.insert(*id))
+            .collect();
+        ordered.extend(sorted_node_ids.into_iter().filter(|id| !seen.contains(id)));
+        ordered
+    }
+
+    /// Map an internal cluster ID to its output value
+    ///
+    /// By default IDs are 1-indexed as per the original format, with
+    /// unassigned nodes as 0; in zero-index mode raw IDs are used and
+    /// unassigned nodes are -1.
+    fn output_cluster_id(&self, cluster_id: Option<usize>) -> i64 {
+        match (cluster_id, self.zero_index_clusters) {
+            (Some(id), false) => id as i64 + 1,
+            (None, false) => 0,
+            (Some(id), true) => id as i64,
+            (None, true) => -1,
+        }
+    }
+
+    /// Output cluster ID of a node with the given visible degree, grouping
+    /// singletons under the unassigned value when collapsing them or when
+    /// zero-indexing (so they can't be mistaken for cluster 0)
+    fn output_node_cluster_id(&self, node: &Patient, degree: usize) -> i64 {
+        if (self.collapse_singletons || self.zero_index_clusters) && degree == 0 {
+            self.output_cluster_id(None)
+        } else {
+            self.output_cluster_id(node.cluster_id)
+        }
+    }
+
     /// Get network statistics
     pub fn get_network_stats(&self) -> HashMap<String, serde_json::Value> {
         let mut stats = HashMap::new();
 
         // Count visible edges
-        let visible_edges = self.edges.iter().filter(|e| e.visible).count();
+        let visible_edges = self.get_edge_count();
         stats.insert("edges".to_string(), serde_json::json!(visible_edges));
 
         // Count nodes
@@ -780,29 +2981,763 @@ impl TransmissionNetwork {
             serde_json::json!(largest_cluster_size),
         );
 
+        // Summarize distances over visible edges
+        let distances: Vec<f64> = self
+            .edges
+            .iter()
+            .filter(|e| e.visible && !e.is_unsupported)
+            .map(|e| e.distance)
+            .collect();
+        // An empty network reports zeros rather than missing values
+        let summary = match describe_vector(&distances) {
+            Some(d) => [d.sum, d.mean, d.median, d.min, d.max],
+            None => [0.0; 5],
+        };
+        let keys = [
+            "total_edge_distance",
+            "mean_edge_distance",
+            "median_edge_distance",
+            "min_edge_distance",
+            "max_edge_distance",
+        ];
+        for (key, value) in keys.into_iter().zip(summary) {
+            stats.insert(key.to_string(), serde_json::json!(value));
+        }
+
         stats
     }
 
+    /// Iterate over all patients in the network, sorted by ID
+    pub fn iter_nodes(&self) -> impl Iterator<Item = &Patient> {
+        let mut nodes: Vec<&Patient> = self.nodes.values().collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        nodes.into_iter()
+    }
+
+    /// Look up a patient by ID
+    pub fn get_node(&self, id: &str) -> Option<&Patient> {
+        self.nodes.get(id)
+    }
+
+    /// Earliest and latest sampling dates of a node, or None if the node is
+    /// unknown or has no dates
+    pub fn node_date_range(&self, id: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        self.nodes.get(id)?.get_date_range()
+    }
+
+    /// Fraction of connected nodes that belong to the largest cluster
+    pub fn giant_component_fraction(&self) -> f64 {
+        let clusters = self.retrieve_clusters(false);
+        let connected_nodes: usize = clusters.values().map(|nodes| nodes.len()).sum();
+        let largest_cluster = clusters.values().map(|nodes| nodes.len()).max();
+
+        match largest_cluster {
+            Some(largest) if connected_nodes > 0 => largest as f64 / connected_nodes as f64,
+            _ => 0.0,
+        }
+    }
+
     /// Get the number of nodes in the network
     pub fn get_node_count(&self) -> usize {
         self.nodes.len()
     }
 
-    /// Get the number of edges in the network
+    /// Get the number of edges in the network (excluding unsupported edges)
     pub fn get_edge_count(&self) -> usize {
-        self.edges.iter().filter(|e| e.visible).count()
+        self.edges
+            .iter()
+            .filter(|e| e.visible && !e.is_unsupported)
+            .count()
     }
 
     /// Convert network to JSON string
     pub fn to_json_string(&self) -> Result<String, NetworkError> {
+        if self.nodes_as_objects_output {
+            return serde_json::to_string(&self.to_json_value()?).map_err(NetworkError::Json);
+        }
         serde_json::to_string(&self.to_json()).map_err(NetworkError::Json)
     }
 
     /// Convert network to pretty-printed JSON string
     pub fn to_json_string_pretty(&self) -> Result<String, NetworkError> {
+        if self.nodes_as_objects_output {
+            return serde_json::to_string_pretty(&self.to_json_value()?)
+                .map_err(NetworkError::Json);
+        }
         serde_json::to_string_pretty(&self.to_json()).map_err(NetworkError::Json)
     }
 
+    /// Stream the network JSON to a writer without building an intermediate string
+    pub fn write_json<W: std::io::Write>(&self, writer: W) -> Result<(), NetworkError> {
+        if self.nodes_as_objects_output {
+            return serde_json::to_writer(writer, &self.to_json_value()?)
+                .map_err(NetworkError::Json);
+        }
+        serde_json::to_writer(writer, &self.to_json()).map_err(NetworkError::Json)
+    }
+
+    /// Stream pretty-printed network JSON to a writer
+    pub fn write_json_pretty<W: std::io::Write>(&self, writer: W) -> Result<(), NetworkError> {
+        if self.nodes_as_objects_output {
+            return serde_json::to_writer_pretty(writer, &self.to_json_value()?)
+                .map_err(NetworkError::Json);
+        }
+        serde_json::to_writer_pretty(writer, &self.to_json()).map_err(NetworkError::Json)
+    }
+
+    /// Stream gzip-compressed, pretty-printed network JSON to a writer,
+    /// compressing on the fly
+    ///
+    /// Decompresses to the same text as `write_json_pretty`. Returns the
+    /// underlying writer once the gzip stream is finished.
+    pub fn write_json_gzip<W: std::io::Write>(&self, writer: W) -> Result<W, NetworkError> {
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        self.write_json_pretty(&mut encoder)?;
+        encoder.finish().map_err(NetworkError::Io)
+    }
+
+    /// Network JSON as a value, with `Nodes` swapped to per-node objects
+    fn to_json_value(&self) -> Result<serde_json::Value, NetworkError> {
+        let mut value = serde_json::to_value(self.to_json()).map_err(NetworkError::Json)?;
+        value["trace_results"]["Nodes"] = serde_json::Value::Array(self.nodes_as_objects());
+        Ok(value)
+    }
+
+    /// Nodes as an array of `{id, cluster, degree, patient_attributes}` objects
+    ///
+    /// Carries the same data, in the same order, as the parallel-array `Nodes`
+    /// section of the JSON output.
+    pub fn nodes_as_objects(&self) -> Vec<serde_json::Value> {
+        let degrees = self.visible_degrees();
+        self.ordered_node_ids()
+            .into_iter()
+            .map(|id| {
+                let node = &self.nodes[id];
+                serde_json::json!({
+                    "id": id,
+                    "cluster": self.output_node_cluster_id(node, degrees[id]),
+                    "degree": degrees[id],
+                    "patient_attributes": {},
+                })
+            })
+            .collect()
+    }
+
+    /// Write the input CSV back out with `source_cluster` and `target_cluster`
+    /// columns appended to every row
+    ///
+    /// Rows and their columns are kept as given; IDs are resolved to nodes the
+    /// same way as when reading with `format`. Cluster IDs match the
+    /// `Nodes.cluster` output, and IDs that are not nodes (e.g. blank) get an
+    /// empty cluster.
+    pub fn annotate_csv_with_clusters(
+        &self,
+        csv_str: &str,
+        format: InputFormat,
+    ) -> Result<String, NetworkError> {
+        let has_headers = self.csv_has_headers(csv_str);
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .has_headers(has_headers)
+            .from_reader(csv_str.as_bytes());
+        let columns = self.edge_columns(&mut reader)?;
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_writer(Vec::new());
+
+        if has_headers {
+            let mut header = reader.headers()?.clone();
+            header.push_field("source_cluster");
+            header.push_field("target_cluster");
+            writer.write_record(&header)?;
+        }
+
+        let degrees = self.visible_degrees();
+        let cluster_of = |id: &str| -> String {
+            let id = id.trim();
+            if is_blank_id(id) {
+                return String::new();
+            }
+            parse_patient_id(id, format, None, &self.aeh_schema, self.id_normalization)
+                .ok()
+                .map(|patient| self.collapse_subject(patient))
+                .and_then(|patient| self.nodes.get(&patient.id))
+                .map(|node| {
+                    self.output_node_cluster_id(node, degrees[&node.id])
+                        .to_string()
+                })
+                .unwrap_or_default()
+        };
+
+        for result in reader.records() {
+            let mut record = result?;
+            let source_cluster = cluster_of(record.get(columns.source).unwrap_or(""));
+            let target_cluster = cluster_of(record.get(columns.target).unwrap_or(""));
+            record.push_field(&source_cluster);
+            record.push_field(&target_cluster);
+            writer.write_record(&record)?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .expect("writing CSV to memory cannot fail");
+        Ok(String::from_utf8(bytes).expect("CSV built from UTF-8 strings"))
+    }
+
+    /// Export cluster membership as NDJSON, one cluster object per line
+    ///
+    /// Only real clusters (2+ connected nodes) are emitted, ordered by cluster ID.
+    pub fn to_clusters_ndjson(&self) -> String {
+        let mut clusters: Vec<(usize, Vec<String>)> = self
+            .retrieve_clusters(false)
+            .into_iter()
+            .filter(|(_, nodes)| nodes.len() > 1)
+            .collect();
+        clusters.sort_by_key(|(id, _)| *id);
+
+        let mut output = String::new();
+        for (id, mut nodes) in clusters {
+            nodes.sort();
+            let line = serde_json::json!({
+                "cluster_id": self.output_cluster_id(Some(id)),
+                "size": nodes.len(),
+                "nodes": nodes,
+            });
+            output.push_str(&line.to_string());
+            output.push('\n');
+        }
+
+        output
+    }
+
+    /// Export the network as Cytoscape.js JSON
+    ///
+    /// Produces `{"elements": {"nodes": [...], "edges": [...]}}`, where node
+    /// `data` carries id, cluster, degree and named attributes, and edge `data`
+    /// carries source, target and distance for the visible edges.
+    pub fn to_cytoscape_json(&self) -> Result<String, NetworkError> {
+        let degrees = self.visible_degrees();
+        let nodes: Vec<serde_json::Value> = self
+            .ordered_node_ids()
+            .into_iter()
+            .map(|id| {
+                let node = &self.nodes[id];
+                serde_json::json!({
+                    "data": {
+                        "id": id,
+                        "cluster": self.output_node_cluster_id(node, degrees[id]),
+                        "degree": degrees[id],
+                        "attributes": node.named_attributes,
+                    }
+                })
+            })
+            .collect();
+
+        let edges: Vec<serde_json::Value> = self
+            .edges
+            .iter()
+            .filter(|e| e.visible && !e.is_unsupported)
+            .enumerate()
+            .map(|(idx, edge)| {
+                serde_json::json!({
+                    "data": {
+                        "id": format!("e{}", idx),
+                        "source": edge.source_id,
+                        "target": edge.target_id,
+                        "distance": edge.distance,
+                    }
+                })
+            })
+            .collect();
+
+        let elements = serde_json::json!({
+            "elements": {
+                "nodes": nodes,
+                "edges": edges,
+            }
+        });
+        serde_json::to_string(&elements).map_err(NetworkError::Json)
+    }
+
+    /// Export the visible adjacency as `{node_id: [neighbor_ids...]}` JSON
+    ///
+    /// Node IDs and neighbor lists are sorted and neighbors deduplicated;
+    /// singletons map to an empty list. Requires `compute_adjacency`.
+    pub fn to_adjacency_json(&self) -> Result<String, NetworkError> {
+        let adjacency: BTreeMap<&str, Vec<&str>> = self
+            .adjacency
+            .iter()
+            .map(|(id, neighbors)| {
+                let mut neighbors: Vec<&str> = neighbors.iter().map(String::as_str).collect();
+                neighbors.sort_unstable();
+                neighbors.dedup();
+                (id.as_str(), neighbors)
+            })
+            .collect();
+
+        serde_json::to_string(&adjacency).map_err(NetworkError::Json)
+    }
+
+    /// List the distinct named attribute keys present across all nodes, sorted
+    pub fn attribute_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .nodes
+            .values()
+            .flat_map(|node| node.named_attributes.keys())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Export a per-node summary table as CSV
+    ///
+    /// Columns are id, cluster, degree, earliest_date and latest_date
+    /// (YYYY-MM-DD), followed by one column per named attribute key across all
+    /// nodes (sorted). Rows follow the same node ordering as `to_json`.
+    pub fn to_node_table_csv(&self) -> String {
+        let node_ids = self.ordered_node_ids();
+        let degrees = self.visible_degrees();
+
+        let attribute_keys = self.attribute_keys();
+
+        let format_date = |date: Option<DateTime<Utc>>| {
+            date.map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default()
+        };
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let mut header = vec!["id", "cluster", "degree", "earliest_date", "latest_date"];
+        header.extend(attribute_keys.iter().map(|key| key.as_str()));
+        writer
+            .write_record(&header)
+            .expect("writing CSV to memory cannot fail");
+
+        for node_id in node_ids {
+            let node = &self.nodes[node_id];
+            let mut row = vec![
+                node.id.clone(),
+                self.output_node_cluster_id(node, degrees[node_id])
+                    .to_string(),
+                degrees[node_id].to_string(),
+                format_date(node.get_earliest_date()),
+                format_date(node.get_most_recent_date()),
+            ];
+            row.extend(
+                attribute_keys
+                    .iter()
+                    .map(|key| node.named_attributes.get(key).cloned().unwrap_or_default()),
+            );
+            writer
+                .write_record(&row)
+                .expect("writing CSV to memory cannot fail");
+        }
+
+        let bytes = writer
+            .into_inner()
+            .expect("writing CSV to memory cannot fail");
+        String::from_utf8(bytes).expect("CSV built from UTF-8 strings")
+    }
+
+    /// Export the visible adjacency as compressed sparse row (CSR) arrays
+    ///
+    /// Returns (row_pointers, column_indices) over the same node ordering as
+    /// `to_json`: the neighbors of node `i` are
+    /// `column_indices[row_pointers[i]..row_pointers[i + 1]]`, sorted and
+    /// deduplicated.
+    pub fn to_csr(&self) -> (Vec<usize>, Vec<usize>) {
+        let node_ids = self.ordered_node_ids();
+        let node_index: HashMap<&str, usize> = node_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
+
+        let mut row_pointers = Vec::with_capacity(node_ids.len() + 1);
+        let mut column_indices = Vec::new();
+        row_pointers.push(0);
+
+        for node_id in &node_ids {
+            let mut neighbors: Vec<usize> = self
+                .adjacency
+                .get(*node_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|neighbor| node_index.get(neighbor.as_str()).copied())
+                .collect();
+            neighbors.sort_unstable();
+            neighbors.dedup();
+
+            column_indices.extend(neighbors);
+            row_pointers.push(column_indices.len());
+        }
+
+        (row_pointers, column_indices)
+    }
+
+    /// Export each cluster as a flat Newick clade, one per line (e.g. `(A,B,C);`)
+    ///
+    /// Only real clusters (2+ connected nodes) are emitted, ordered by cluster
+    /// ID, with members sorted and no branch lengths.
+    pub fn to_cluster_newick(&self) -> String {
+        let mut clusters: Vec<(usize, Vec<String>)> = self
+            .retrieve_clusters(false)
+            .into_iter()
+            .filter(|(_, nodes)| nodes.len() > 1)
+            .collect();
+        clusters.sort_by_key(|(id, _)| *id);
+
+        let mut output = String::new();
+        for (_, mut nodes) in clusters {
+            nodes.sort();
+            let labels: Vec<String> = nodes.iter().map(|id| newick_label(id)).collect();
+            output.push_str(&format!("({});\n", labels.join(",")));
+        }
+
+        output
+    }
+
+    /// Find edges whose endpoints have different values for a named attribute
+    ///
+    /// Returns (source, target, source value, target value) for visible edges,
+    /// sorted. Edges where either endpoint lacks the attribute are skipped.
+    pub fn cross_attribute_edges(&self, key: &str) -> Vec<(String, String, String, String)> {
+        let mut crossing: Vec<(String, String, String, String)> = self
+            .edges
+            .iter()
+            .filter(|e| e.visible && !e.is_unsupported)
+            .filter_map(|edge| {
+                let source_value = self.nodes.get(&edge.source_id)?.named_attributes.get(key)?;
+                let target_value = self.nodes.get(&edge.target_id)?.named_attributes.get(key)?;
+                (source_value != target_value).then(|| {
+                    (
+                        edge.source_id.clone(),
+                        edge.target_id.clone(),
+                        source_value.clone(),
+                        target_value.clone(),
+                    )
+                })
+            })
+            .collect();
+        crossing.sort();
+        crossing
+    }
+
+    /// Check whether two nodes exist and share a cluster
+    pub fn same_cluster(&self, a: &str, b: &str) -> bool {
+        let cluster_a = self.nodes.get(a).and_then(|node| node.cluster_id);
+        let cluster_b = self.nodes.get(b).and_then(|node| node.cluster_id);
+        cluster_a.is_some() && cluster_a == cluster_b
+    }
+
+    /// Find a shortest chain of nodes linking `from` to `to`
+    ///
+    /// Uses breadth-first search over the visible adjacency, so the path has
+    /// the fewest edges. Returns None if either node is missing or they are
+    /// not connected.
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        self.shortest_path_with_distance(from, to)
+            .map(|(path, _)| path)
+    }
+
+    /// Find a shortest chain of nodes linking `from` to `to`, with the summed
+    /// edge distance along it
+    pub fn shortest_path_with_distance(&self, from: &str, to: &str) -> Option<(Vec<String>, f64)> {
+        if !self.nodes.contains_key(from) || !self.nodes.contains_key(to) {
+            return None;
+        }
+
+        // BFS recording each node's predecessor
+        let mut predecessors: HashMap<&str, &str> = HashMap::new();
+        let mut visited: HashSet<&str> = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(node_id) = queue.pop_front() {
+            if node_id == to {
+                break;
+            }
+            for neighbor_id in self.adjacency.get(node_id).into_iter().flatten() {
+                if visited.insert(neighbor_id) {
+                    predecessors.insert(neighbor_id, node_id);
+                    queue.push_back(neighbor_id);
+                }
+            }
+        }
+
+        if !visited.contains(to) {
+            return None;
+        }
+
+        // Walk back from the target, summing edge distances
+        let mut path = vec![to.to_string()];
+        let mut total_distance = 0.0;
+        let mut current = to;
+        while let Some(&previous) = predecessors.get(current) {
+            let key = self.normalized_key(previous, current);
+            if let Some(&edge_idx) = self.edge_lookup.get(&key) {
+                total_distance += self.edges[edge_idx].distance;
+            }
+            path.push(previous.to_string());
+            current = previous;
+        }
+        path.reverse();
+
+        Some((path, total_distance))
+    }
+
+    /// Find every node reachable from `start` through edges within a
+    /// per-edge distance cap
+    ///
+    /// The cap defaults to the network threshold and is compared using the
+    /// configured threshold mode. The result includes `start` itself, and is
+    /// empty if `start` is not in the network.
+    pub fn reachable_from(&self, start: &str, max_distance: Option<f64>) -> HashSet<String> {
+        if !self.nodes.contains_key(start) {
+            return HashSet::new();
+        }
+        let cap = max_distance.or_else(|| self.metadata.get("threshold").and_then(|v| v.as_f64()));
+
+        let mut reached: HashSet<&str> = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node_id) = queue.pop_front() {
+            for neighbor_id in self.adjacency.get(node_id).into_iter().flatten() {
+                if reached.contains(neighbor_id.as_str()) {
+                    continue;
+                }
+                let within_cap = match cap {
+                    Some(cap) => self
+                        .edge_lookup
+                        .get(&self.normalized_key(node_id, neighbor_id))
+                        .is_some_and(|&idx| {
+                            self.threshold_mode.passes(self.edges[idx].distance, cap)
+                        }),
+                    None => true,
+                };
+                if within_cap {
+                    reached.insert(neighbor_id);
+                    queue.push_back(neighbor_id);
+                }
+            }
+        }
+
+        reached.into_iter().map(str::to_string).collect()
+    }
+
+    /// Compute a stable hash of the network structure
+    ///
+    /// Covers the sorted node IDs and the sorted normalized visible edges with
+    /// their exact distances, so networks with the same structure hash equally
+    /// regardless of input order. Based on SHA-256, so the value is also stable
+    /// across runs and builds.
+    pub fn structure_hash(&self) -> u64 {
+        let mut node_ids: Vec<&String> = self.nodes.keys().collect();
+        node_ids.sort();
+
+        let mut edges: Vec<((String, String), u64)> = self
+            .edges
+            .iter()
+            .filter(|e| e.visible && !e.is_unsupported)
+            .map(|e| {
+                (
+                    self.normalized_key(&e.source_id, &e.target_id),
+                    e.distance.to_bits(),
+                )
+            })
+            .collect();
+        edges.sort();
+
+        // NUL separates fields and newline separates records
+        let mut hasher = Sha256::new();
+        for id in node_ids {
+            hasher.update(id.as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.update(b"\0edges\n");
+        for ((source, target), distance) in edges {
+            hasher.update(source.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(target.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(distance.to_le_bytes());
+            hasher.update(b"\n");
+        }
+
+        let digest = hasher.finalize();
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Score how confidently each node belongs to its cluster
+    ///
+    /// Each visible edge scores `(threshold - distance) / margin`, clamped to
+    /// [0, 1], so edges at least `margin` below the threshold score 1 and
+    /// edges right at it score 0. A node takes the score of its strongest
+    /// edge; singletons score 0. A non-positive margin scores every edge 1.
+    pub fn membership_confidence(&self, margin: f64) -> HashMap<String, f64> {
+        let threshold = self
+            .metadata
+            .get("threshold")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.015);
+
+        let mut confidence: HashMap<String, f64> =
+            self.nodes.keys().map(|id| (id.clone(), 0.0)).collect();
+        for edge in self.edges.iter().filter(|e| e.visible && !e.is_unsupported) {
+            let score = if margin > 0.0 {
+                ((threshold - edge.distance) / margin).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            for id in [&edge.source_id, &edge.target_id] {
+                if let Some(value) = confidence.get_mut(id) {
+                    *value = value.max(score);
+                }
+            }
+        }
+
+        confidence
+    }
+
+    /// Suggest pairs of node IDs that may be typos of each other
+    ///
+    /// Returns sorted pairs of distinct IDs within `max_edit_distance`
+    /// (Levenshtein) of each other, for a human to review. Nothing is merged.
+    pub fn suggest_id_merges(&self, max_edit_distance: usize) -> Vec<(String, String)> {
+        let mut ids: Vec<&String> = self.nodes.keys().collect();
+        ids.sort();
+
+        let mut suggestions = Vec::new();
+        for (i, a) in ids.iter().enumerate() {
+            let a_len = a.chars().count();
+            for b in &ids[i + 1..] {
+                // The length difference is a lower bound on the edit distance
+                if a_len.abs_diff(b.chars().count()) > max_edit_distance {
+                    continue;
+                }
+                if levenshtein(a, b) <= max_edit_distance {
+                    suggestions.push(((*a).clone(), (*b).clone()));
+                }
+            }
+        }
+
+        suggestions
+    }
+
+    /// Check that derived state agrees with the edge list
+    ///
+    /// Verifies that `edge_lookup` indices point to edges with matching keys,
+    /// that `adjacency` matches the visible, supported edges, that degrees
+    /// match adjacency, and that linked nodes share a cluster. Returns every
+    /// violation found.
+    pub fn validate_invariants(&self) -> Result<(), Vec<String>> {
+        let mut violations = Vec::new();
+
+        // edge_lookup <-> edges
+        for (key, &idx) in &self.edge_lookup {
+            match self.edges.get(idx) {
+                Some(edge) if edge.get_key() == *key => {}
+                Some(_) => violations.push(format!(
+                    "edge_lookup entry {}-{} points to edge {} with a different key",
+                    key.0, key.1, idx
+                )),
+                None => violations.push(format!(
+                    "edge_lookup entry {}-{} points past the end of edges ({})",
+                    key.0, key.1, idx
+                )),
+            }
+        }
+        for (idx, edge) in self.edges.iter().enumerate() {
+            if self.edge_lookup.get(&edge.get_key()) != Some(&idx) {
+                violations.push(format!(
+                    "edge {} ({}-{}) is missing from edge_lookup",
+                    idx, edge.source_id, edge.target_id
+                ));
+            }
+        }
+
+        // adjacency <-> visible edges
+        let mut expected: HashMap<&str, Vec<&str>> = self
+            .nodes
+            .keys()
+            .map(|id| (id.as_str(), Vec::new()))
+            .collect();
+        for edge in self.edges.iter().filter(|e| e.visible && !e.is_unsupported) {
+            for (a, b) in [
+                (&edge.source_id, &edge.target_id),
+                (&edge.target_id, &edge.source_id),
+            ] {
+                match expected.get_mut(a.as_str()) {
+                    Some(neighbors) => neighbors.push(b),
+                    None => violations.push(format!("edge endpoint {} is not a node", a)),
+                }
+            }
+        }
+        let mut node_ids: Vec<&String> = self.nodes.keys().collect();
+        node_ids.sort();
+        for id in node_ids {
+            let mut actual: Vec<&str> = self
+                .adjacency
+                .get(id)
+                .map(|neighbors| neighbors.iter().map(String::as_str).collect())
+                .unwrap_or_default();
+            actual.sort();
+            let mut wanted = expected.remove(id.as_str()).unwrap_or_default();
+            wanted.sort();
+            if actual != wanted {
+                violations.push(format!(
+                    "adjacency of {} is {:?}, expected {:?}",
+                    id, actual, wanted
+                ));
+            }
+
+            // degree <-> adjacency, and clusters across links
+            let node = &self.nodes[id];
+            if node.degree != actual.len() {
+                violations.push(format!(
+                    "degree of {} is {}, but it has {} neighbors",
+                    id,
+                    node.degree,
+                    actual.len()
+                ));
+            }
+            for neighbor in actual {
+                let neighbor_cluster = self.nodes.get(neighbor).and_then(|n| n.cluster_id);
+                if node.cluster_id.is_some() && node.cluster_id != neighbor_cluster {
+                    violations.push(format!(
+                        "{} and its neighbor {} are in different clusters",
+                        id, neighbor
+                    ));
+                }
+            }
+        }
+        for id in self.adjacency.keys() {
+            if !self.nodes.contains_key(id) {
+                violations.push(format!("adjacency has an entry for unknown node {}", id));
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Rebuild all derived state (`edge_lookup`, adjacency, degrees and
+    /// clusters) from the edge list
+    pub fn repair(&mut self) {
+        self.edge_lookup.clear();
+        for (idx, edge) in self.edges.iter().enumerate() {
+            self.edge_lookup.entry(edge.get_key()).or_insert(idx);
+        }
+
+        // Rebuilding the adjacency also rederives the degrees
+        self.compute_adjacency();
+        self.compute_clusters();
+    }
+
     /// Check if a node has connections (degree > 0)
     pub fn is_node_connected(&self, node_id: &str) -> bool {
         self.nodes
@@ -811,3 +3746,135 @@ impl TransmissionNetwork {
             .unwrap_or(false)
     }
 }
+
+/// Check whether a raw ID is empty once whitespace and any surrounding quotes
+/// (e.g. an unquoted-by-the-parser `""`) are stripped
+fn is_blank_id(id: &str) -> bool {
+    let trimmed = id.trim();
+    let unquoted = trimmed
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .unwrap_or(trimmed);
+    unquoted.trim().is_empty()
+}
+
+/// Quote a Newick label if it contains characters with special meaning
+fn newick_label(id: &str) -> String {
+    let needs_quoting = id
+        .chars()
+        .any(|c| c.is_whitespace() || "()[],:;'".contains(c));
+    if needs_quoting {
+        format!("'{}'", id.replace('\'', "''"))
+    } else {
+        id.to_string()
+    }
+}
+
+/// Find the root of a node in a union-find parent array (with path halving)
+fn find_root(parent: &mut [usize], mut idx: usize) -> usize {
+    while parent[idx] != idx {
+        parent[idx] = parent[parent[idx]];
+        idx = parent[idx];
+    }
+    idx
+}
+
+/// Edge betweenness of an undirected graph given as an adjacency list
+/// (Brandes' algorithm), keyed by lexically ordered node pairs
+fn brandes_edge_betweenness(
+    adjacency: &HashMap<String, Vec<String>>,
+) -> HashMap<(String, String), f64> {
+    let mut node_ids: Vec<&String> = adjacency.keys().collect();
+    node_ids.sort();
+    let node_index: HashMap<&str, usize> = node_ids
+        .iter()
+        .enumerate()
+        .map(|(idx, id)| (id.as_str(), idx))
+        .collect();
+    let neighbors: Vec<Vec<usize>> = node_ids
+        .iter()
+        .map(|id| {
+            adjacency[*id]
+                .iter()
+                .filter_map(|neighbor| node_index.get(neighbor.as_str()).copied())
+                .collect()
+        })
+        .collect();
+
+    let n = node_ids.len();
+    let mut betweenness: HashMap<(usize, usize), f64> = HashMap::new();
+
+    for source in 0..n {
+        // BFS counting shortest paths from the source
+        let mut order = Vec::new();
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut path_counts = vec![0.0; n];
+        let mut distances: Vec<Option<usize>> = vec![None; n];
+        path_counts[source] = 1.0;
+        distances[source] = Some(0);
+
+        let mut queue = VecDeque::from([source]);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            let next_distance = distances[v].map(|d| d + 1);
+            for &w in &neighbors[v] {
+                if distances[w].is_none() {
+                    distances[w] = next_distance;
+                    queue.push_back(w);
+                }
+                if distances[w] == next_distance {
+                    path_counts[w] += path_counts[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+
+        // Accumulate dependencies in reverse BFS order
+        let mut dependency = vec![0.0; n];
+        for &w in order.iter().rev() {
+            for &v in &predecessors[w] {
+                let contribution = path_counts[v] / path_counts[w] * (1.0 + dependency[w]);
+                *betweenness.entry((v.min(w), v.max(w))).or_insert(0.0) += contribution;
+                dependency[v] += contribution;
+            }
+        }
+    }
+
+    // Every path was counted from both ends
+    betweenness
+        .into_iter()
+        .map(|((a, b), value)| ((node_ids[a].clone(), node_ids[b].clone()), value / 2.0))
+        .collect()
+}
+
+/// Connected components of an adjacency list, largest first, each sorted by ID
+fn connected_components(adjacency: &HashMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    let mut node_ids: Vec<&String> = adjacency.keys().collect();
+    node_ids.sort();
+
+    let mut visited: HashSet<&str> = HashSet::new();
+    let mut components = Vec::new();
+
+    for start_id in node_ids {
+        if !visited.insert(start_id) {
+            continue;
+        }
+
+        let mut component = vec![start_id.clone()];
+        let mut queue = VecDeque::from([start_id.as_str()]);
+        while let Some(node_id) = queue.pop_front() {
+            for neighbor_id in adjacency.get(node_id).into_iter().flatten() {
+                if adjacency.contains_key(neighbor_id) && visited.insert(neighbor_id) {
+                    component.push(neighbor_id.clone());
+                    queue.push_back(neighbor_id);
+                }
+            }
+        }
+
+        component.sort();
+        components.push(component);
+    }
+
+    components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    components
+}
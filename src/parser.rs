@@ -1,18 +1,41 @@
-use crate::types::{InputFormat, NetworkError, ParsedPatient};
+use crate::types::{AehFieldSchema, IdNormalization, InputFormat, NetworkError, ParsedPatient};
 use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use regex::Regex;
+use std::sync::OnceLock;
+
+/// ISO date (YYYY-MM-DD) embedded in an ID, compiled once and shared by the
+/// plain-date and regex formats
+fn iso_date_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(\d{4}-\d{2}-\d{2})").unwrap())
+}
 
 /// Parse a patient ID based on the specified format
 pub fn parse_patient_id(
     id: &str,
     format: InputFormat,
     default_date: Option<DateTime<Utc>>,
+    aeh_schema: &AehFieldSchema,
+    normalization: IdNormalization,
 ) -> Result<ParsedPatient, NetworkError> {
-    match format {
+    let mut patient = match format {
         InputFormat::Plain => parse_plain_id(id, default_date),
-        InputFormat::AEH => parse_aeh_id(id),
+        InputFormat::PlainWithDate => parse_plain_id_with_date(id, default_date),
+        InputFormat::AEH => parse_aeh_id(id, aeh_schema),
         InputFormat::LANL => parse_lanl_id(id),
         InputFormat::Regex => parse_regex_id(id, default_date),
+    }?;
+
+    patient.id = normalize_id(&patient.id, normalization);
+    Ok(patient)
+}
+
+/// Apply the configured normalization to a node ID
+fn normalize_id(id: &str, normalization: IdNormalization) -> String {
+    match normalization {
+        IdNormalization::None => id.to_string(),
+        IdNormalization::TrimOnly => id.trim().to_string(),
+        IdNormalization::Lowercase => id.trim().to_lowercase(),
     }
 }
 
@@ -29,8 +52,24 @@ fn parse_plain_id(
     Ok(patient)
 }
 
+/// Parse a plain ID, extracting an embedded ISO date while keeping the full ID
+fn parse_plain_id_with_date(
+    id: &str,
+    default_date: Option<DateTime<Utc>>,
+) -> Result<ParsedPatient, NetworkError> {
+    let mut patient = parse_plain_id(id, default_date)?;
+
+    if let Some(date_match) = iso_date_pattern().find(&patient.id) {
+        if let Ok(date) = parse_date(date_match.as_str()) {
+            patient.date = Some(date);
+        }
+    }
+
+    Ok(patient)
+}
+
 /// Parse an AEH format ID (ID | date | other fields)
-fn parse_aeh_id(id: &str) -> Result<ParsedPatient, NetworkError> {
+fn parse_aeh_id(id: &str, schema: &AehFieldSchema) -> Result<ParsedPatient, NetworkError> {
     let parts: Vec<&str> = id.split('|').collect();
 
     if parts.is_empty() || parts[0].trim().is_empty() {
@@ -42,24 +81,25 @@ fn parse_aeh_id(id: &str) -> Result<ParsedPatient, NetworkError> {
 
     let patient_id = parts[0].trim().to_string();
 
-    // Extract date if available (field index 1)
-    let date = if parts.len() > 1 && !parts[1].trim().is_empty() {
-        match parse_date(parts[1].trim()) {
-            Ok(date) => Some(date),
-            Err(_) => None,
-        }
-    } else {
-        None
+    // Extract date if available (field index 1 by default)
+    let date = match parts.get(schema.date_index) {
+        Some(field) if !field.trim().is_empty() => parse_date(field.trim()).ok(),
+        _ => None,
     };
 
     // Create patient
     let mut patient = ParsedPatient::new(patient_id, date);
 
-    // Extract additional attributes (field index 2+)
-    for (i, field) in parts.iter().enumerate().skip(2) {
-        if !field.trim().is_empty() {
-            patient.add_attribute(&format!("field_{}", i), field.trim().to_string());
+    // Extract additional attributes (all fields other than the ID and date)
+    for (i, field) in parts.iter().enumerate().skip(1) {
+        if i == schema.date_index || field.trim().is_empty() {
+            continue;
         }
+        let name = match schema.field_names.get(&i) {
+            Some(name) => name.clone(),
+            None => format!("field_{}", i),
+        };
+        patient.add_attribute(&name, field.trim().to_string());
     }
 
     Ok(patient)
@@ -128,12 +168,10 @@ fn parse_regex_id(
     // regex patterns and named capture groups
 
     // Example: Try to extract an ISO date (YYYY-MM-DD) and ID from a string
-    let iso_date_pattern = Regex::new(r"(\d{4}-\d{2}-\d{2})").unwrap();
-
     let mut patient = ParsedPatient::new(id.to_string(), default_date);
 
     // Extract date if present
-    if let Some(date_match) = iso_date_pattern.find(id) {
+    if let Some(date_match) = iso_date_pattern().find(id) {
         if let Ok(date) = parse_date(date_match.as_str()) {
             patient.date = Some(date);
 
@@ -148,6 +186,33 @@ fn parse_regex_id(
     Ok(patient)
 }
 
+/// Find a date field in an ID that is present but cannot be parsed
+///
+/// Only formats with a dedicated date field (AEH, LANL) are checked; the
+/// offending field is returned.
+pub fn unparseable_date(
+    id: &str,
+    format: InputFormat,
+    aeh_schema: &AehFieldSchema,
+) -> Option<String> {
+    let field = match format {
+        InputFormat::AEH => id.split('|').nth(aeh_schema.date_index)?.trim(),
+        InputFormat::LANL => id.split('_').nth(3)?.trim(),
+        _ => return None,
+    };
+    if field.is_empty() {
+        return None;
+    }
+
+    let parsed = match format {
+        InputFormat::LANL => field
+            .parse::<i32>()
+            .is_ok_and(|year| (1900..=2100).contains(&year)),
+        _ => parse_date(field).is_ok(),
+    };
+    (!parsed).then(|| field.to_string())
+}
+
 /// Parse a date string into a DateTime<Utc>
 pub fn parse_date(date_str: &str) -> Result<DateTime<Utc>, NetworkError> {
     // Try common date formats
@@ -1,7 +1,9 @@
+use crate::utils::natural_cmp;
 use chrono::{DateTime, Utc};
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 use thiserror::Error;
 
 /// Error types for network operations
@@ -24,6 +26,12 @@ pub enum NetworkError {
 
     #[error("JSON serialization error: {0}")]
     Json(#[from] serde_json::Error),
+
+    #[error("Network size limit exceeded: {0}")]
+    SizeLimit(String),
+
+    #[error("Conflicting attribute values: {0}")]
+    AttributeConflict(String),
 }
 
 /// Available input formats for parsing node IDs
@@ -35,10 +43,217 @@ pub enum InputFormat {
     LANL,
     /// Plain ID with no metadata
     Plain,
+    /// Plain ID kept whole, with an embedded ISO date (YYYY-MM-DD) extracted
+    PlainWithDate,
     /// Custom regex format
     Regex,
 }
 
+impl FromStr for InputFormat {
+    type Err = NetworkError;
+
+    /// Parse a format name (`aeh`, `lanl`, `plain`, `plain-date` or `regex`,
+    /// case-insensitive) as used by the CLI and WASM bindings
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "aeh" => Ok(InputFormat::AEH),
+            "lanl" => Ok(InputFormat::LANL),
+            "plain" => Ok(InputFormat::Plain),
+            "plain-date" => Ok(InputFormat::PlainWithDate),
+            "regex" => Ok(InputFormat::Regex),
+            _ => Err(NetworkError::Format(format!(
+                "unknown input format: {}",
+                name
+            ))),
+        }
+    }
+}
+
+/// Normalization applied to parsed node IDs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdNormalization {
+    /// Leave parsed IDs as-is
+    None,
+    /// Trim surrounding whitespace
+    #[default]
+    TrimOnly,
+    /// Trim and lowercase, so case variants collapse into one node
+    Lowercase,
+}
+
+/// Definition used for the "Sequences used to make links" summary count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SequenceCountMode {
+    /// Number of nodes with at least one edge
+    #[default]
+    ConnectedNodes,
+    /// Number of distinct samples (dates) across nodes with at least one edge
+    DistinctSamples,
+}
+
+impl SequenceCountMode {
+    /// Label describing this definition in the JSON settings
+    pub fn label(&self) -> &'static str {
+        match self {
+            SequenceCountMode::ConnectedNodes => "connected nodes",
+            SequenceCountMode::DistinctSamples => "distinct samples",
+        }
+    }
+}
+
+/// Ordering used to normalize edge endpoints (source before target)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdOrdering {
+    /// Plain string comparison ("N10" < "N9")
+    #[default]
+    Lexical,
+    /// Numeric-aware comparison ("N9" < "N10")
+    Natural,
+}
+
+impl IdOrdering {
+    /// Compare two IDs under this ordering
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            IdOrdering::Lexical => a.cmp(b),
+            IdOrdering::Natural => natural_cmp(a, b),
+        }
+    }
+}
+
+/// How edge distances are compared against the clustering threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThresholdMode {
+    /// Keep edges with distance <= threshold
+    #[default]
+    Inclusive,
+    /// Keep edges with distance < threshold
+    Exclusive,
+}
+
+impl ThresholdMode {
+    /// Check whether a distance passes the threshold under this mode
+    pub fn passes(&self, distance: f64, threshold: f64) -> bool {
+        match self {
+            ThresholdMode::Inclusive => distance <= threshold,
+            ThresholdMode::Exclusive => distance < threshold,
+        }
+    }
+}
+
+/// Limits applied when reading untrusted CSV input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvLimits {
+    /// Maximum length of a raw input line, in bytes
+    pub max_line_length: usize,
+    /// Maximum length of a single parsed field, in bytes
+    pub max_field_length: usize,
+}
+
+impl Default for CsvLimits {
+    fn default() -> Self {
+        CsvLimits {
+            max_line_length: 64 * 1024,
+            max_field_length: 4 * 1024,
+        }
+    }
+}
+
+/// Scheme for giving clusters human-readable names
+#[derive(Debug, Clone)]
+pub enum ClusterNaming {
+    /// Prefix plus a zero-padded rank by size, largest first (e.g. `Cluster-001`)
+    Prefix(String),
+    /// Callback mapping (output cluster ID, sorted member IDs) to a name
+    Callback(fn(i64, &[String]) -> String),
+}
+
+/// Calendar bins used to bucket node dates (e.g. for epidemic curves)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateBucket {
+    /// ISO-8601 week, labeled like "2020-W03"
+    IsoWeek,
+    /// Calendar month, labeled like "2020-01"
+    Month,
+}
+
+impl DateBucket {
+    /// Label of the bin containing a date
+    pub fn label(&self, date: &DateTime<Utc>) -> String {
+        match self {
+            DateBucket::IsoWeek => date.format("%G-W%V").to_string(),
+            DateBucket::Month => date.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// A data-quality issue noticed while reading input in lenient mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// 1-based input line (0 if unknown)
+    pub line: u64,
+    pub message: String,
+}
+
+/// Why an edge was left out of clustering (see `filtered_edges`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterReason {
+    /// Distance did not pass the clustering threshold
+    AboveThreshold,
+    /// Distance fell below the minimum threshold
+    BelowMinThreshold,
+    /// Support fell below the support cutoff
+    LowSupport,
+    /// An endpoint was flagged as a contaminant (not currently produced;
+    /// contaminant screening is not implemented)
+    Contaminant,
+    /// The edge was marked invisible
+    Invisible,
+}
+
+/// How a node's named attribute is resolved when rows give it different values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AttributeConflictPolicy {
+    /// Keep the value from the first row
+    FirstWins,
+    /// Keep the value from the last row
+    #[default]
+    LastWins,
+    /// Reject the input with `NetworkError::AttributeConflict`
+    Error,
+    /// Keep the first value, and every distinct value in `collected_attributes`
+    CollectAll,
+}
+
+/// Header names of the edge columns in a CSV with a header row, for exports
+/// where the IDs and distance are not the first three columns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub source: String,
+    pub target: String,
+    pub distance: String,
+    /// Optional edge support column
+    pub support: Option<String>,
+}
+
+/// Field layout for AEH-style pipe-delimited IDs (ID | date | other fields)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AehFieldSchema {
+    /// Pipe-field index holding the sample date
+    pub date_index: usize,
+    /// Attribute names for other fields by pipe-field index (unnamed fields become `field_N`)
+    pub field_names: HashMap<usize, String>,
+}
+
+impl Default for AehFieldSchema {
+    fn default() -> Self {
+        AehFieldSchema {
+            date_index: 1,
+            field_names: HashMap::new(),
+        }
+    }
+}
+
 /// A node in the network representing a patient
 #[derive(Debug, Clone, PartialEq)]
 pub struct Patient {
@@ -53,6 +268,9 @@ pub struct Patient {
     pub treatment_naive: Option<bool>,
     pub attributes: HashSet<String>,
     pub named_attributes: HashMap<String, String>,
+    /// Every distinct value seen per named attribute, in first-seen order
+    /// (only filled under `AttributeConflictPolicy::CollectAll`)
+    pub collected_attributes: HashMap<String, Vec<String>>,
 }
 
 impl Patient {
@@ -70,6 +288,7 @@ impl Patient {
             treatment_naive: None,
             attributes: HashSet::new(),
             named_attributes: HashMap::new(),
+            collected_attributes: HashMap::new(),
         }
     }
 
@@ -106,6 +325,50 @@ impl Patient {
         }
     }
 
+    /// Merge a named attribute value from another input row, resolving a
+    /// differing existing value with the given policy
+    pub fn merge_named_attribute(
+        &mut self,
+        key: &str,
+        value: &str,
+        policy: AttributeConflictPolicy,
+    ) -> Result<(), NetworkError> {
+        if value.is_empty() {
+            return Ok(());
+        }
+
+        if policy == AttributeConflictPolicy::CollectAll {
+            let values = self
+                .collected_attributes
+                .entry(key.to_string())
+                .or_default();
+            if !values.iter().any(|v| v == value) {
+                values.push(value.to_string());
+            }
+        }
+
+        match self.named_attributes.get(key) {
+            Some(existing) if existing != value => match policy {
+                AttributeConflictPolicy::LastWins => {
+                    self.named_attributes
+                        .insert(key.to_string(), value.to_string());
+                }
+                AttributeConflictPolicy::Error => {
+                    return Err(NetworkError::AttributeConflict(format!(
+                        "node '{}' has {} = '{}' and '{}'",
+                        self.id, key, existing, value
+                    )));
+                }
+                AttributeConflictPolicy::FirstWins | AttributeConflictPolicy::CollectAll => {}
+            },
+            _ => {
+                self.named_attributes
+                    .insert(key.to_string(), value.to_string());
+            }
+        }
+        Ok(())
+    }
+
     /// Increment the degree (number of connections) for this patient
     pub fn increment_degree(&mut self) {
         self.degree += 1;
@@ -115,6 +378,16 @@ impl Patient {
     pub fn get_most_recent_date(&self) -> Option<DateTime<Utc>> {
         self.dates.iter().filter_map(|&date| date).max()
     }
+
+    /// Get the earliest date if available
+    pub fn get_earliest_date(&self) -> Option<DateTime<Utc>> {
+        self.dates.iter().filter_map(|&date| date).min()
+    }
+
+    /// Get the earliest and latest dates if available
+    pub fn get_date_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        Some((self.get_earliest_date()?, self.get_most_recent_date()?))
+    }
 }
 
 impl Hash for Patient {
@@ -140,7 +413,10 @@ pub struct Edge {
     pub attributes: HashSet<String>,
     pub sequences: Option<Vec<String>>,
     pub distance: f64,
+    pub support: Option<f64>,
     pub is_unsupported: bool,
+    /// Whether source/target were swapped from the input orientation during normalization
+    pub orientation_swapped: bool,
 }
 
 impl Edge {
@@ -151,6 +427,69 @@ impl Edge {
         source_date: Option<DateTime<Utc>>,
         target_date: Option<DateTime<Utc>>,
         distance: f64,
+    ) -> Result<Self, NetworkError> {
+        Self::new_with_ordering(
+            source_id,
+            target_id,
+            source_date,
+            target_date,
+            distance,
+            IdOrdering::Lexical,
+        )
+    }
+
+    /// Create a new edge, or `None` for a self-loop (instead of an error)
+    ///
+    /// For paths where equal endpoints are expected and should be dropped,
+    /// such as nodes merged by renaming.
+    pub fn try_new(
+        source_id: String,
+        target_id: String,
+        source_date: Option<DateTime<Utc>>,
+        target_date: Option<DateTime<Utc>>,
+        distance: f64,
+    ) -> Result<Option<Self>, NetworkError> {
+        Self::try_new_with_ordering(
+            source_id,
+            target_id,
+            source_date,
+            target_date,
+            distance,
+            IdOrdering::Lexical,
+        )
+    }
+
+    /// Create a new edge with the given ID ordering, or `None` for a self-loop
+    pub fn try_new_with_ordering(
+        source_id: String,
+        target_id: String,
+        source_date: Option<DateTime<Utc>>,
+        target_date: Option<DateTime<Utc>>,
+        distance: f64,
+        ordering: IdOrdering,
+    ) -> Result<Option<Self>, NetworkError> {
+        if source_id == target_id {
+            return Ok(None);
+        }
+        Self::new_with_ordering(
+            source_id,
+            target_id,
+            source_date,
+            target_date,
+            distance,
+            ordering,
+        )
+        .map(Some)
+    }
+
+    /// Create a new edge, normalizing endpoints with the given ID ordering
+    pub fn new_with_ordering(
+        source_id: String,
+        target_id: String,
+        source_date: Option<DateTime<Utc>>,
+        target_date: Option<DateTime<Utc>>,
+        distance: f64,
+        ordering: IdOrdering,
     ) -> Result<Self, NetworkError> {
         // Ensure no self-loops
         if source_id == target_id {
@@ -159,10 +498,11 @@ impl Edge {
 
         // Always normalize source_id and target_id to ensure source_id < target_id
         // This maintains consistent edge representation
-        let (source_id, target_id, source_date, target_date) = if source_id < target_id {
-            (source_id, target_id, source_date, target_date)
-        } else {
+        let orientation_swapped = ordering.compare(&source_id, &target_id) == Ordering::Greater;
+        let (source_id, target_id, source_date, target_date) = if orientation_swapped {
             (target_id, source_id, target_date, source_date)
+        } else {
+            (source_id, target_id, source_date, target_date)
         };
 
         Ok(Edge {
@@ -174,7 +514,9 @@ impl Edge {
             attributes: HashSet::new(),
             sequences: None,
             distance,
+            support: None,
             is_unsupported: false,
+            orientation_swapped,
         })
     }
 
@@ -225,6 +567,38 @@ impl Edge {
         source_date_ok && target_date_ok
     }
 
+    /// Get the (source, target) pair in the orientation it was originally given
+    pub fn original_orientation(&self) -> (&str, &str) {
+        if self.orientation_swapped {
+            (&self.target_id, &self.source_id)
+        } else {
+            (&self.source_id, &self.target_id)
+        }
+    }
+
+    /// Inferred transmission direction relative to the normalized orientation
+    ///
+    /// Returns 1 if the source was sampled first (source -> target), -1 if the
+    /// target was sampled first, and 0 if dates are missing or equal.
+    pub fn direction(&self) -> i8 {
+        match (self.source_date, self.target_date) {
+            (Some(source), Some(target)) if source < target => 1,
+            (Some(source), Some(target)) if source > target => -1,
+            _ => 0,
+        }
+    }
+
+    /// Get the reason this edge is excluded from the network, if any
+    pub fn removal_reason(&self) -> Option<&'static str> {
+        if !self.visible {
+            Some("hidden")
+        } else if self.is_unsupported {
+            Some("unsupported")
+        } else {
+            None
+        }
+    }
+
     /// Get the edge key (source_id, target_id) for consistent lookup
     pub fn get_key(&self) -> (String, String) {
         (self.source_id.clone(), self.target_id.clone())
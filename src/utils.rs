@@ -1,5 +1,9 @@
 use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
 use std::cmp::Ordering;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
 
 /// Calculate time difference between two dates in days
 pub fn date_difference_days(date1: &DateTime<Utc>, date2: &DateTime<Utc>) -> i64 {
@@ -17,6 +21,241 @@ pub fn compare_dates(date1: &Option<DateTime<Utc>>, date2: &Option<DateTime<Utc>
     }
 }
 
+/// Compare two strings treating runs of digits as numbers ("N9" < "N10")
+///
+/// Ties (e.g. "N01" vs "N1") fall back to plain string comparison so the
+/// ordering stays total and deterministic.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let mut a_digits = String::new();
+                while let Some(c) = a_chars.next_if(|c| c.is_ascii_digit()) {
+                    a_digits.push(c);
+                }
+                let mut b_digits = String::new();
+                while let Some(c) = b_chars.next_if(|c| c.is_ascii_digit()) {
+                    b_digits.push(c);
+                }
+
+                // Compare numerically without overflow: fewer significant digits is smaller
+                let a_trimmed = a_digits.trim_start_matches('0');
+                let b_trimmed = b_digits.trim_start_matches('0');
+                let ordering = a_trimmed
+                    .len()
+                    .cmp(&b_trimmed.len())
+                    .then_with(|| a_trimmed.cmp(b_trimmed));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(ca), Some(cb)) => {
+                let ordering = ca.cmp(cb);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// Levenshtein (edit) distance between two strings, counted in characters
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    let mut current = vec![0; b_chars.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b_chars.len()]
+}
+
+/// Summary statistics for a vector of values
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorDescription {
+    pub count: usize,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub iqr: (f64, f64),
+}
+
+/// Describe a vector of values (count, sum, min, max, mean, median, IQR)
+pub fn describe_vector(values: &[f64]) -> Option<VectorDescription> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let count = sorted.len();
+    let sum: f64 = sorted.iter().sum();
+    let median = if count % 2 == 1 {
+        sorted[count / 2]
+    } else {
+        0.5 * (sorted[count / 2 - 1] + sorted[count / 2])
+    };
+
+    Some(VectorDescription {
+        count,
+        sum,
+        min: sorted[0],
+        max: sorted[count - 1],
+        mean: sum / count as f64,
+        median,
+        iqr: (
+            interpolate_sorted(&sorted, 25.0),
+            interpolate_sorted(&sorted, 75.0),
+        ),
+    })
+}
+
+/// Compute a percentile (0-100) of a vector of values using linear interpolation
+pub fn percentile(values: &[f64], percentile: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    Some(interpolate_sorted(&sorted, percentile))
+}
+
+/// Linearly interpolated percentile (0-100) of a non-empty, sorted slice
+fn interpolate_sorted(sorted: &[f64], percentile: f64) -> f64 {
+    let last = sorted.len() - 1;
+    let position = (percentile.clamp(0.0, 100.0) / 100.0) * last as f64;
+    let lower = (position.floor() as usize).min(last);
+    let upper = (position.ceil() as usize).min(last);
+    let fraction = position - lower as f64;
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * fraction
+}
+
+/// Compute the SHA-256 digest of some bytes as a lowercase hex string
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Write a file atomically: write to a temp file in the same directory, then rename
+///
+/// The destination is either left untouched or replaced with the complete contents.
+pub fn write_atomic<P: AsRef<Path>>(path: P, contents: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+/// Write a file atomically, streaming the contents through a callback
+///
+/// The callback writes to the temp file; it is synced and renamed over the
+/// destination only if the callback succeeds.
+pub fn write_atomic_with<P, E, F>(path: P, write: F) -> Result<(), E>
+where
+    P: AsRef<Path>,
+    E: From<io::Error>,
+    F: FnOnce(&mut fs::File) -> Result<(), E>,
+{
+    let path = path.as_ref();
+    let file_name = match path.file_name() {
+        Some(name) => name,
+        None => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Path has no file name").into())
+        }
+    };
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        write(&mut file)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Names of the optional cargo features this crate was compiled with
+pub fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "annotation") {
+        features.push("annotation");
+    }
+    if cfg!(feature = "parallel") {
+        features.push("parallel");
+    }
+    features
+}
+
+/// Version text shown by `--version` in the binaries: `<bin> <version>`, then
+/// the enabled optional features (or `none`) on a second line
+pub fn version_string(bin: &str) -> String {
+    let features = enabled_features();
+    let features = if features.is_empty() {
+        "none".to_string()
+    } else {
+        features.join(", ")
+    };
+    format!(
+        "{} {}\nfeatures: {}",
+        bin,
+        env!("CARGO_PKG_VERSION"),
+        features
+    )
+}
+
+/// Small deterministic pseudo-random generator (SplitMix64) for seeded sampling
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform index in 0..bound (bound must be non-zero)
+    pub(crate) fn next_index(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
 /// Set up logging for WASM
 #[cfg(target_arch = "wasm32")]
 pub fn setup_logging() {
@@ -32,6 +271,23 @@ pub fn log_to_console(message: &str) {
     console::log_1(&wasm_bindgen::JsValue::from_str(message));
 }
 
+/// Round a float value to the specified number of significant figures
+/// (at least one); zero and non-finite values are returned unchanged
+pub fn round_to_significant_figures(value: f64, figures: usize) -> f64 {
+    if value == 0.0 || !value.is_finite() {
+        return value;
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    let decimals = figures.max(1) as i32 - 1 - magnitude;
+    if decimals >= 0 {
+        let factor = 10f64.powi(decimals);
+        (value * factor).round() / factor
+    } else {
+        let factor = 10f64.powi(-decimals);
+        (value / factor).round() * factor
+    }
+}
+
 /// Format a float value with the specified number of decimal places
 pub fn format_float(value: f64, decimals: usize) -> String {
     format!("{:.*}", decimals, value)
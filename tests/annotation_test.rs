@@ -1,4 +1,4 @@
-use hivcluster_rs::annotate_network;
+use hivcluster_rs::{annotate_edges, annotate_network, validate_annotation_inputs, InputFormat, TransmissionNetwork};
 use serde_json::{json, Value};
 
 #[test]
@@ -285,4 +285,268 @@ fn test_annotation_with_enum_type() {
     let nodes = result_json["Nodes"].as_array().unwrap();
     assert_eq!(nodes[0]["patient_attributes"]["category"], "A");
     assert_eq!(nodes[1]["patient_attributes"]["category"], "B");
-}
\ No newline at end of file
+}
+#[test]
+fn test_annotation_with_enum_colors() {
+    let mut network = TransmissionNetwork::new();
+    network.read_from_csv_str("A,B,0.01\n", 0.03, InputFormat::Plain).unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    let network_json = network.to_json_string().unwrap();
+
+    let attributes_json = json!([
+        {"ehars_uid": "A", "risk": "MSM", "stage": "Acute"},
+        {"ehars_uid": "B", "risk": "PWID", "stage": "Chronic"}
+    ]).to_string();
+
+    // "risk" maps values to colors (one left uncolored); "stage" is a plain enum
+    let schema_json = json!({
+        "risk": {
+            "type": "enum",
+            "label": "Risk group",
+            "enum": [
+                {"value": "MSM", "color": "#1f77b4"},
+                {"value": "PWID", "color": "#ff7f0e"},
+                {"value": "HET"}
+            ]
+        },
+        "stage": {"type": "enum", "label": "Stage", "enum": ["Acute", "Chronic"]}
+    }).to_string();
+
+    let result = annotate_network(&network_json, &attributes_json, &schema_json).unwrap();
+    let result_json: Value = serde_json::from_str(&result).unwrap();
+    let schema = &result_json["trace_results"]["patient_attribute_schema"];
+
+    assert_eq!(schema["risk"]["enum"], json!(["MSM", "PWID", "HET"]));
+    assert_eq!(schema["risk"]["enum_colors"], json!({"MSM": "#1f77b4", "PWID": "#ff7f0e"}));
+
+    assert_eq!(schema["stage"]["enum"], json!(["Acute", "Chronic"]));
+    assert!(schema["stage"].get("enum_colors").is_none());
+}
+
+#[test]
+fn test_annotation_node_filter() {
+    // Build a real network: A-B-C chain plus D-E pair
+    let mut network = TransmissionNetwork::new();
+    network.read_from_csv_str("A,B,0.01\nB,C,0.01\nD,E,0.01\n", 0.03, InputFormat::Plain).unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    let network_json = network.to_json_string().unwrap();
+
+    let attributes_json = json!([
+        {"ehars_uid": "A", "country": "US"},
+        {"ehars_uid": "B", "country": "Canada"},
+        {"ehars_uid": "C", "country": "US"},
+        {"ehars_uid": "D", "country": "US"},
+        {"ehars_uid": "E", "country": "US"}
+    ]).to_string();
+
+    let schema_json = json!({
+        "country": {"type": "String", "label": "Country"},
+        "filter": {"field": "country", "values": ["US"]}
+    }).to_string();
+
+    let result = annotate_network(&network_json, &attributes_json, &schema_json).unwrap();
+    let result_json: Value = serde_json::from_str(&result).unwrap();
+    let trace = &result_json["trace_results"];
+
+    // B is dropped from every Nodes array
+    let ids: Vec<&str> = trace["Nodes"]["id"].as_array().unwrap()
+        .iter().map(|v| v.as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["A", "C", "D", "E"]);
+    assert_eq!(trace["Nodes"]["cluster"].as_array().unwrap().len(), 4);
+    assert_eq!(trace["Nodes"]["patient_attributes"].as_array().unwrap().len(), 4);
+
+    // Only D-E survives, re-indexed to the pruned node arrays
+    assert_eq!(trace["Edges"]["source"], json!([2]));
+    assert_eq!(trace["Edges"]["target"], json!([3]));
+    assert_eq!(trace["Edges"]["length"].as_array().unwrap().len(), 1);
+    assert_eq!(trace["Edges"]["removed"]["values"].as_array().unwrap().len(), 1);
+
+    // The filter is configuration, not an attribute
+    assert!(trace["patient_attribute_schema"].get("filter").is_none());
+}
+
+#[test]
+fn test_annotation_is_additive() {
+    let mut network = TransmissionNetwork::new();
+    network.read_from_csv_str("A,B,0.01\nC,D,0.01\n", 0.03, InputFormat::Plain).unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    let network_json = network.to_json_string().unwrap();
+
+    // First source: country
+    let countries = json!([
+        {"ehars_uid": "A", "country": "US"},
+        {"ehars_uid": "B", "country": "Canada"},
+        {"ehars_uid": "C", "country": "US"}
+    ]).to_string();
+    let country_schema = json!({"country": {"type": "String", "label": "Country"}}).to_string();
+    let annotated = annotate_network(&network_json, &countries, &country_schema).unwrap();
+
+    // Second source: risk, which doesn't mention country
+    let risks = json!([
+        {"ehars_uid": "A", "risk": "MSM"},
+        {"ehars_uid": "D", "risk": "PWID"}
+    ]).to_string();
+    let risk_schema = json!({"risk": {"type": "String", "label": "Risk"}}).to_string();
+    let result = annotate_network(&annotated, &risks, &risk_schema).unwrap();
+    let result_json: Value = serde_json::from_str(&result).unwrap();
+    let trace = &result_json["trace_results"];
+
+    let ids: Vec<&str> = trace["Nodes"]["id"].as_array().unwrap()
+        .iter().map(|v| v.as_str().unwrap()).collect();
+    let attrs = |id: &str| &trace["Nodes"]["patient_attributes"][ids.iter().position(|i| *i == id).unwrap()];
+
+    // Both sources survive on the nodes
+    assert_eq!(attrs("A")["country"], "US");
+    assert_eq!(attrs("A")["risk"], "MSM");
+    assert_eq!(attrs("B")["country"], "Canada");
+    assert_eq!(attrs("B")["risk"], "");
+    assert_eq!(attrs("D")["country"], "");
+    assert_eq!(attrs("D")["risk"], "PWID");
+
+    // ...and in the schema and missing-value counts
+    assert_eq!(trace["patient_attribute_schema"]["country"]["label"], "Country");
+    assert_eq!(trace["patient_attribute_schema"]["risk"]["label"], "Risk");
+    assert_eq!(trace["patient_attribute_missing_counts"], json!({"country": 1, "risk": 2}));
+
+    // Re-annotating a field updates it without touching the others
+    let update = json!([{"ehars_uid": "B", "country": "Mexico"}]).to_string();
+    let result = annotate_network(&result, &update, &country_schema).unwrap();
+    let result_json: Value = serde_json::from_str(&result).unwrap();
+    let trace = &result_json["trace_results"];
+    let attrs = |id: &str| &trace["Nodes"]["patient_attributes"][ids.iter().position(|i| *i == id).unwrap()];
+    assert_eq!(attrs("B")["country"], "Mexico");
+    assert_eq!(attrs("A")["country"], "US");
+    assert_eq!(attrs("A")["risk"], "MSM");
+}
+
+#[test]
+fn test_annotation_with_array_values() {
+    let mut network = TransmissionNetwork::new();
+    network.read_from_csv_str("A,B,0.01\n", 0.03, InputFormat::Plain).unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    let network_json = network.to_json_string().unwrap();
+
+    let attributes_json = json!([
+        {"ehars_uid": "A", "risk": ["MSM", "IDU"], "region": ["North"]}
+    ]).to_string();
+
+    // "risk" declares its type; "region" has it inferred from the values
+    let schema_json = json!({
+        "risk": {"type": "Array", "label": "Risk factors"},
+        "region": {"label": "Region"}
+    }).to_string();
+
+    let result = annotate_network(&network_json, &attributes_json, &schema_json).unwrap();
+    let result_json: Value = serde_json::from_str(&result).unwrap();
+    let trace = &result_json["trace_results"];
+
+    // Array values survive as arrays, and missing ones default to empty arrays
+    let attrs = trace["Nodes"]["patient_attributes"].as_array().unwrap();
+    assert_eq!(attrs[0]["risk"], json!(["MSM", "IDU"]));
+    assert_eq!(attrs[0]["region"], json!(["North"]));
+    assert_eq!(attrs[1]["risk"], json!([]));
+
+    assert_eq!(trace["patient_attribute_schema"]["risk"]["type"], "Array");
+    assert_eq!(trace["patient_attribute_schema"]["region"]["type"], "Array");
+}
+
+#[test]
+fn test_validate_annotation_inputs() {
+    let attributes_json = json!([
+        {"ehars_uid": "A", "country": "US"},
+        {"ehars_uid": "B", "country": "Canada"},
+        {"country": "US"}
+    ]).to_string();
+
+    let schema_json = json!({
+        "country": {"type": "String", "label": "Country"},
+        "risk": {"type": "String", "label": "Risk factor"},
+        "keying": {"fields": ["ehars_uid"], "delimiter": "~"}
+    }).to_string();
+
+    let report = validate_annotation_inputs(&attributes_json, &schema_json).unwrap();
+    assert_eq!(report.record_count, 3);
+    assert_eq!(report.covered_fields, vec!["country"]);
+
+    // "risk" appears in no record; "keying" is configuration, not a field
+    assert_eq!(report.missing_fields, vec!["risk"]);
+    assert_eq!(report.missing_key_records, 1);
+}
+
+#[test]
+fn test_annotation_missing_counts() {
+    let mut network = TransmissionNetwork::new();
+    network.read_from_csv_str("A,B,0.01\nC,D,0.01\n", 0.03, InputFormat::Plain).unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    let network_json = network.to_json_string().unwrap();
+
+    // "country" is given for every node, "risk" for only half of them
+    let attributes_json = json!([
+        {"ehars_uid": "A", "country": "US", "risk": "MSM"},
+        {"ehars_uid": "B", "country": "US", "risk": null},
+        {"ehars_uid": "C", "country": "Canada", "risk": "IDU"},
+        {"ehars_uid": "D", "country": "Canada"}
+    ]).to_string();
+
+    let schema_json = json!({
+        "country": {"type": "String", "label": "Country"},
+        "risk": {"type": "String", "label": "Risk factor"}
+    }).to_string();
+
+    let result = annotate_network(&network_json, &attributes_json, &schema_json).unwrap();
+    let result_json: Value = serde_json::from_str(&result).unwrap();
+    let missing = &result_json["trace_results"]["patient_attribute_missing_counts"];
+
+    assert_eq!(missing["country"], 0);
+    assert_eq!(missing["risk"], 2);
+}
+
+#[test]
+fn test_annotate_edges_bootstrap() {
+    let mut network = TransmissionNetwork::new();
+    network.read_from_csv_str("A,B,0.01\nB,C,0.02\n", 0.03, InputFormat::Plain).unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    let network_json = network.to_json_string().unwrap();
+
+    // Keyed in the opposite orientation to the input row
+    let edge_attributes_json = json!([
+        {"source_id": "C", "target_id": "B", "bootstrap": 0.97}
+    ]).to_string();
+
+    let schema_json = json!({
+        "bootstrap": {"type": "Number", "label": "Bootstrap support"}
+    }).to_string();
+
+    let result = annotate_edges(&network_json, &edge_attributes_json, &schema_json).unwrap();
+    let result_json: Value = serde_json::from_str(&result).unwrap();
+    let trace = &result_json["trace_results"];
+
+    let ids = trace["Nodes"]["id"].as_array().unwrap();
+    let sources = trace["Edges"]["source"].as_array().unwrap();
+    let targets = trace["Edges"]["target"].as_array().unwrap();
+    let edge_attrs = trace["Edges"]["attributes"]["edge_attributes"].as_array().unwrap();
+    assert_eq!(edge_attrs.len(), sources.len());
+
+    for (i, attrs) in edge_attrs.iter().enumerate() {
+        let mut pair = vec![
+            ids[sources[i].as_u64().unwrap() as usize].as_str().unwrap(),
+            ids[targets[i].as_u64().unwrap() as usize].as_str().unwrap(),
+        ];
+        pair.sort();
+        if pair == ["B", "C"] {
+            assert_eq!(attrs["bootstrap"], 0.97);
+        } else {
+            assert_eq!(attrs["bootstrap"], "");
+        }
+    }
+
+    let schema_entry = &trace["edge_attribute_schema"]["bootstrap"];
+    assert_eq!(schema_entry["type"], "Number");
+    assert_eq!(schema_entry["label"], "Bootstrap support");
+}
@@ -0,0 +1,308 @@
+use std::fs;
+use std::process::Command;
+
+const TEST_CSV: &str = "ID1,ID2,0.01
+ID1,ID3,0.02
+ID5,ID6,0.03
+";
+
+#[test]
+fn test_cli_writes_output_atomically() {
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("input.csv");
+    let output_path = dir.path().join("output.json");
+    fs::write(&input_path, TEST_CSV).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_hivcluster"))
+        .arg(&input_path)
+        .args(["-t", "0.03", "-o"])
+        .arg(&output_path)
+        .status()
+        .expect("Failed to run hivcluster");
+    assert!(status.success(), "hivcluster should exit successfully");
+
+    // The output should be complete, parseable JSON
+    let output = fs::read_to_string(&output_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["trace_results"]["Network Summary"]["Nodes"], 5);
+
+    // No temp file should be left behind
+    let entries: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(
+        entries.len(),
+        2,
+        "Unexpected files left behind: {:?}",
+        entries
+    );
+}
+
+#[test]
+fn test_cli_percent_distances() {
+    let dir = tempfile::tempdir().unwrap();
+    let fraction_path = dir.path().join("fraction.csv");
+    let percent_path = dir.path().join("percent.csv");
+    fs::write(&fraction_path, TEST_CSV).unwrap();
+    fs::write(&percent_path, "ID1,ID2,1\nID1,ID3,2\nID5,ID6,3\n").unwrap();
+
+    let run = |input: &std::path::Path, extra: &[&str]| -> serde_json::Value {
+        let output = Command::new(env!("CARGO_BIN_EXE_hivcluster"))
+            .arg(input)
+            .args(["-t", "0.025"])
+            .args(extra)
+            .output()
+            .expect("Failed to run hivcluster");
+        assert!(
+            output.status.success(),
+            "hivcluster should exit successfully"
+        );
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    let fraction = run(&fraction_path, &[]);
+    let percent = run(&percent_path, &["--percent"]);
+
+    // ID5-ID6 (3%) is above the threshold in both runs
+    assert_eq!(percent["trace_results"]["Network Summary"]["Edges"], 2);
+    assert_eq!(
+        percent["trace_results"]["Edges"]["source"],
+        fraction["trace_results"]["Edges"]["source"]
+    );
+    assert_eq!(
+        percent["trace_results"]["Edges"]["target"],
+        fraction["trace_results"]["Edges"]["target"]
+    );
+}
+
+#[test]
+fn test_cli_provenance_hash() {
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("input.csv");
+    fs::write(&input_path, TEST_CSV).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hivcluster"))
+        .arg(&input_path)
+        .args(["-t", "0.03", "--provenance"])
+        .output()
+        .expect("Failed to run hivcluster");
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let provenance = &json["trace_results"]["provenance"];
+    assert_eq!(
+        provenance["input_sha256"],
+        hivcluster_rs::sha256_hex(TEST_CSV.as_bytes())
+    );
+    assert_eq!(provenance["input_file"], input_path.to_str().unwrap());
+}
+
+#[test]
+fn test_cli_gzip_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("input.csv");
+    let output_path = dir.path().join("output.json");
+    fs::write(&input_path, TEST_CSV).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_hivcluster"))
+        .arg(&input_path)
+        .args(["-t", "0.03", "--gzip", "-o"])
+        .arg(&output_path)
+        .status()
+        .expect("Failed to run hivcluster");
+    assert!(status.success());
+
+    // The .gz extension is appended, and the contents decompress to JSON
+    let compressed = fs::read(dir.path().join("output.json.gz")).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_reader(flate2::read::GzDecoder::new(compressed.as_slice())).unwrap();
+    assert_eq!(json["trace_results"]["Network Summary"]["Nodes"], 5);
+    assert!(!output_path.exists());
+}
+
+#[test]
+fn test_cli_split_clusters() {
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("input.csv");
+    let split_dir = dir.path().join("clusters");
+    // Two clusters (ID1-ID2-ID3, ID5-ID6) and two singletons (ID7, ID8)
+    fs::write(&input_path, format!("{}ID7,ID8,0.5\n", TEST_CSV)).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_hivcluster"))
+        .arg(&input_path)
+        .args(["-t", "0.03", "--split-clusters"])
+        .arg(&split_dir)
+        .status()
+        .expect("Failed to run hivcluster");
+    assert!(status.success());
+
+    let mut files: Vec<String> = fs::read_dir(&split_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    assert_eq!(files.len(), 3, "Unexpected files: {:?}", files);
+    assert_eq!(
+        files.iter().filter(|f| f.starts_with("cluster_")).count(),
+        2
+    );
+    assert!(files.contains(&"singletons.json".to_string()));
+
+    // Each cluster file holds just that cluster
+    let mut sizes: Vec<u64> = files
+        .iter()
+        .filter(|f| f.starts_with("cluster_"))
+        .map(|f| {
+            let json: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(split_dir.join(f)).unwrap()).unwrap();
+            json["trace_results"]["Network Summary"]["Nodes"]
+                .as_u64()
+                .unwrap()
+        })
+        .collect();
+    sizes.sort();
+    assert_eq!(sizes, vec![2, 3]);
+}
+
+#[test]
+fn test_cli_split_clusters_keep_cluster_ids() {
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("input.csv");
+    let output_path = dir.path().join("output.json");
+    let split_dir = dir.path().join("clusters");
+    fs::write(&input_path, format!("{}ID7,ID8,0.5\n", TEST_CSV)).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_hivcluster"))
+        .arg(&input_path)
+        .args(["-t", "0.03", "-o"])
+        .arg(&output_path)
+        .arg("--split-clusters")
+        .arg(&split_dir)
+        .status()
+        .expect("Failed to run hivcluster");
+    assert!(status.success());
+
+    // Map node ID -> cluster from the Nodes section of an output file
+    let node_clusters = |path: &std::path::Path| -> Vec<(String, i64)> {
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        let nodes = &json["trace_results"]["Nodes"];
+        let ids = nodes["id"].as_array().unwrap();
+        let clusters = nodes["cluster"].as_array().unwrap();
+        ids.iter()
+            .zip(clusters)
+            .map(|(id, cluster)| (id.as_str().unwrap().to_string(), cluster.as_i64().unwrap()))
+            .collect()
+    };
+    let combined: std::collections::HashMap<String, i64> =
+        node_clusters(&output_path).into_iter().collect();
+
+    let mut checked = 0;
+    for entry in fs::read_dir(&split_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        for (id, cluster) in node_clusters(&path) {
+            assert_eq!(cluster, combined[&id], "cluster of {} in {}", id, name);
+            if let Some(file_id) = name.strip_prefix("cluster_") {
+                assert_eq!(cluster.to_string(), file_id);
+            }
+            checked += 1;
+        }
+    }
+    assert_eq!(checked, combined.len());
+}
+
+#[test]
+fn test_cli_version() {
+    for (name, binary) in [
+        ("hivcluster", env!("CARGO_BIN_EXE_hivcluster")),
+        ("hivannotate", env!("CARGO_BIN_EXE_hivannotate")),
+    ] {
+        let output = Command::new(binary)
+            .arg("--version")
+            .output()
+            .expect("Failed to run binary");
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains(env!("CARGO_PKG_VERSION")));
+
+        let features: Vec<&str> = hivcluster_rs::enabled_features();
+        let expected = if features.is_empty() {
+            "features: none".to_string()
+        } else {
+            format!("features: {}", features.join(", "))
+        };
+        assert!(stdout.contains(&expected), "unexpected output: {}", stdout);
+
+        // Both binaries share one version format
+        assert_eq!(stdout.trim_end(), hivcluster_rs::version_string(name));
+    }
+}
+
+#[test]
+fn test_hivannotate_csv_attributes() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut network = hivcluster_rs::TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, hivcluster_rs::InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    let network_path = dir.path().join("network.json");
+    fs::write(&network_path, network.to_json_string().unwrap()).unwrap();
+
+    let schema_path = dir.path().join("schema.json");
+    fs::write(
+        &schema_path,
+        r#"{"country": {"type": "String", "label": "Country"},
+            "risk": {"type": "String", "label": "Risk"}}"#,
+    )
+    .unwrap();
+
+    // The same records as JSON and as a spreadsheet export (with an empty cell)
+    let json_path = dir.path().join("attributes.json");
+    fs::write(
+        &json_path,
+        r#"[{"ehars_uid": "ID1", "country": "US", "risk": "MSM"},
+            {"ehars_uid": "ID2", "country": "Canada"},
+            {"ehars_uid": "ID5", "country": "US", "risk": "PWID"}]"#,
+    )
+    .unwrap();
+    let csv_path = dir.path().join("attributes.csv");
+    fs::write(
+        &csv_path,
+        "ehars_uid,country,risk\nID1,US,MSM\nID2,Canada,\nID5,US,PWID\n",
+    )
+    .unwrap();
+
+    let annotate = |attributes: &std::path::Path| {
+        let output = Command::new(env!("CARGO_BIN_EXE_hivannotate"))
+            .arg("-n")
+            .arg(&network_path)
+            .arg("-a")
+            .arg(attributes)
+            .arg("-s")
+            .arg(&schema_path)
+            .output()
+            .expect("Failed to run hivannotate");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let from_csv = annotate(&csv_path);
+    assert_eq!(from_csv, annotate(&json_path));
+
+    let trace = &from_csv["trace_results"];
+    let ids = trace["Nodes"]["id"].as_array().unwrap();
+    let idx = ids.iter().position(|id| id == "ID1").unwrap();
+    assert_eq!(
+        trace["Nodes"]["patient_attributes"][idx]["risk"],
+        serde_json::json!("MSM")
+    );
+}
@@ -1,4 +1,7 @@
-use hivcluster_rs::{InputFormat, TransmissionNetwork};
+use hivcluster_rs::{
+    ClusterNaming, Edge, FilterReason, IdOrdering, InputFormat, NetworkError, TransmissionNetwork,
+};
+use std::collections::{HashMap, HashSet};
 
 const TEST_CSV: &str = r#"ID1,ID2,0.01
 ID1,ID3,0.02
@@ -284,3 +287,1336 @@ fn test_error_cases() {
     let result = network.read_from_csv_str(invalid_dist_csv, 0.03, InputFormat::Plain);
     assert!(result.is_err(), "Should error on invalid distance value");
 }
+
+#[test]
+fn test_duplicate_edge_support_flip() {
+    // A-B loses support in its closer duplicate; C-D gains it
+    let csv = "A,B,0.02,0.9\nA,B,0.01,0.2\nC,D,0.02,0.2\nC,D,0.01,0.9\n";
+
+    let mut network = TransmissionNetwork::new();
+    network.support_cutoff = Some(0.5);
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+
+    // Incremental adjacency and degrees follow the replacement edge
+    assert_eq!(network.get_node("A").unwrap().degree, 0);
+    assert_eq!(network.get_node("B").unwrap().degree, 0);
+    assert!(network.adjacency["A"].is_empty());
+    assert_eq!(network.get_node("C").unwrap().degree, 1);
+    assert_eq!(network.get_node("D").unwrap().degree, 1);
+    assert_eq!(network.adjacency["C"], vec!["D".to_string()]);
+    assert_eq!(network.validate_invariants(), Ok(()));
+
+    network.compute_clusters();
+    assert!(!network.same_cluster("A", "B"));
+    assert!(network.same_cluster("C", "D"));
+}
+
+#[test]
+fn test_unsupported_edges() {
+    // ID2-ID3 has low support and should not join the two pairs
+    let csv = "ID1,ID2,0.01,0.95\nID2,ID3,0.01,0.2\nID3,ID4,0.01,0.9\n";
+
+    let mut network = TransmissionNetwork::new();
+    network.support_cutoff = Some(0.5);
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    assert_eq!(
+        network.get_edge_count(),
+        2,
+        "Unsupported edge should not count"
+    );
+    assert_eq!(
+        network.edges.len(),
+        3,
+        "Unsupported edge should be retained"
+    );
+
+    let clusters = network.retrieve_clusters(false);
+    assert_eq!(
+        clusters.len(),
+        2,
+        "Unsupported edge should not merge clusters"
+    );
+
+    let json = network.to_json();
+    let edges = &json.trace_results.edges;
+    assert_eq!(edges.source.len(), 3, "Unsupported edge should be reported");
+    assert_eq!(
+        edges.removed.values.iter().filter(|&&v| v == 1).count(),
+        1,
+        "Unsupported edge should be marked as removed"
+    );
+    assert_eq!(json.trace_results.network_summary.Edges, 2);
+}
+
+#[test]
+fn test_strict_columns() {
+    let csv = "ID1,ID2,0.01\nID2,ID3,0.01,0.2,0.3,0.4,0.5,0.6,0.7,0.8\n";
+
+    // Flexible mode (default) ignores the extra columns
+    let mut network = TransmissionNetwork::new();
+    assert!(network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .is_ok());
+
+    // Strict mode rejects the wide row and cites its line
+    let mut network = TransmissionNetwork::new();
+    network.strict_columns = true;
+    let err = network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("line 2"),
+        "Error should cite the offending row: {}",
+        err
+    );
+}
+
+#[test]
+fn test_stable_cluster_ids() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let previous = network.cluster_assignment();
+    let id1_cluster = previous["ID1"];
+
+    // Break up the ID7-ID8 cluster, leaving the ID1 cluster untouched
+    for edge in network.edges.iter_mut() {
+        if edge.source_id == "ID7" && edge.target_id == "ID8" {
+            edge.visible = false;
+        }
+    }
+    network.compute_adjacency();
+    network.compute_clusters_stable(&previous);
+
+    let current = network.cluster_assignment();
+    assert_eq!(
+        current["ID1"], id1_cluster,
+        "Untouched cluster keeps its ID"
+    );
+    assert_eq!(
+        current["ID4"], id1_cluster,
+        "Untouched cluster keeps its ID"
+    );
+    assert_eq!(
+        current["ID5"], previous["ID5"],
+        "Untouched cluster keeps its ID"
+    );
+}
+
+#[test]
+fn test_node_access() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let node = network.get_node("ID1").expect("ID1 should exist");
+    assert_eq!(node.degree, 2, "ID1 should have degree 2");
+    assert!(
+        node.cluster_id.is_some(),
+        "ID1 should be assigned a cluster"
+    );
+    assert!(network.get_node("missing").is_none());
+
+    let ids: Vec<&str> = network.iter_nodes().map(|node| node.id.as_str()).collect();
+    assert_eq!(ids.len(), 8, "Should iterate all 8 nodes");
+    assert_eq!(ids[0], "ID1", "Nodes should be iterated in ID order");
+}
+
+#[test]
+fn test_cluster_sizes_with_ids() {
+    let mut network = TransmissionNetwork::new();
+    network.cluster_sizes_with_ids = true;
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let json = network.to_json();
+    let sizes = json
+        .trace_results
+        .cluster_sizes_with_ids
+        .expect("Sizes with IDs should be emitted");
+
+    let size_values: Vec<usize> = sizes.iter().map(|c| c.size).collect();
+    assert_eq!(
+        size_values,
+        vec![4, 2, 2],
+        "Largest cluster should come first"
+    );
+
+    // IDs should match the cluster assigned to member nodes in the output
+    let ids = &json.trace_results.nodes.id;
+    let clusters = &json.trace_results.nodes.cluster;
+    let id1_idx = ids.iter().position(|id| id == "ID1").unwrap();
+    assert_eq!(sizes[0].id, clusters[id1_idx]);
+
+    // Legacy ascending sizes are kept
+    assert_eq!(json.trace_results.cluster_sizes, vec![2, 2, 4]);
+}
+
+#[test]
+fn test_zero_index_clusters() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let one_based = network.to_json().trace_results.nodes.cluster;
+    network.zero_index_clusters = true;
+    let zero_based = network.to_json().trace_results.nodes.cluster;
+
+    assert_eq!(one_based.len(), zero_based.len());
+    for (one, zero) in one_based.iter().zip(zero_based.iter()) {
+        assert_eq!(*one, zero + 1, "Indices should shift by exactly one");
+    }
+    assert!(zero_based.contains(&0), "Zero-based IDs should start at 0");
+
+    // Nodes without a cluster assignment use the -1 sentinel
+    for node in network.nodes.values_mut() {
+        node.cluster_id = None;
+    }
+    let unassigned = network.to_json().trace_results.nodes.cluster;
+    assert!(unassigned.iter().all(|&c| c == -1));
+
+    // Singletons (ID5 and ID6 at this threshold) use it too, so they can't be
+    // mistaken for cluster 0
+    let mut network = TransmissionNetwork::new();
+    network.zero_index_clusters = true;
+    network
+        .read_from_csv_str(TEST_CSV, 0.025, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    let json = network.to_json();
+    let ids = &json.trace_results.nodes.id;
+    let clusters = &json.trace_results.nodes.cluster;
+    let cluster_of = |id: &str| clusters[ids.iter().position(|i| i == id).unwrap()];
+    assert_eq!(cluster_of("ID5"), -1);
+    assert_eq!(cluster_of("ID6"), -1);
+    assert_eq!(cluster_of("ID1"), 0);
+    assert_eq!(cluster_of("ID7"), 1);
+}
+
+#[test]
+fn test_bridge_edges() {
+    // Two triangles joined by a single edge (A3-B1)
+    let csv =
+        "A1,A2,0.01\nA2,A3,0.01\nA1,A3,0.01\nA3,B1,0.01\nB1,B2,0.01\nB2,B3,0.01\nB1,B3,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+
+    let bridges = network.bridge_edges();
+    assert_eq!(
+        bridges,
+        vec![("A3".to_string(), "B1".to_string())],
+        "Only the joining edge should be a bridge"
+    );
+}
+
+#[test]
+fn test_bridge_edges_natural_ordering() {
+    // A path n2 - n10 - n3, where lexical and natural ordering disagree
+    let mut network = TransmissionNetwork::new();
+    network.id_ordering = IdOrdering::Natural;
+    network
+        .read_from_csv_str("n10,n2,0.01\nn3,n10,0.01\n", 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+
+    let bridges = network.bridge_edges();
+    assert_eq!(
+        bridges,
+        vec![
+            ("n2".to_string(), "n10".to_string()),
+            ("n3".to_string(), "n10".to_string()),
+        ]
+    );
+
+    // Bridge keys can be looked up like any other edge key
+    for key in &bridges {
+        let idx = network.edge_lookup[key];
+        assert_eq!(&network.edges[idx].get_key(), key);
+    }
+}
+
+#[test]
+fn test_hidden_edges_reported_as_removed() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+
+    // Hide the ID7-ID8 edge
+    for edge in network.edges.iter_mut() {
+        if edge.source_id == "ID7" && edge.target_id == "ID8" {
+            edge.visible = false;
+        }
+    }
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // Hidden edges are dropped from the output by default
+    let json = network.to_json();
+    assert_eq!(json.trace_results.edges.source.len(), 4);
+
+    network.include_hidden_edges = true;
+    let json = network.to_json();
+    let edges = &json.trace_results.edges;
+    assert_eq!(edges.source.len(), 5, "Hidden edge should be listed");
+    assert_eq!(json.trace_results.network_summary.Edges, 4);
+
+    let hidden_idx = edges
+        .sequences
+        .iter()
+        .position(|pair| pair == &vec!["ID7".to_string(), "ID8".to_string()])
+        .expect("Hidden edge should appear in the Edges section");
+    assert_eq!(edges.removed.values[hidden_idx], 1);
+    assert_eq!(edges.length[hidden_idx], 0.01);
+    assert_eq!(
+        edges.removed.reasons.get(&hidden_idx.to_string()),
+        Some(&"hidden".to_string())
+    );
+    assert_eq!(edges.removed.reasons.len(), 1);
+}
+
+#[test]
+fn test_original_edge_orientation() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str("B,A,0.01\nC,D,0.01\n", 0.03, InputFormat::Plain)
+        .unwrap();
+
+    let swapped = network
+        .edges
+        .iter()
+        .find(|edge| edge.source_id == "A")
+        .expect("Edge should be normalized to A-B");
+    assert_eq!(swapped.target_id, "B");
+    assert!(swapped.orientation_swapped);
+    assert_eq!(swapped.original_orientation(), ("B", "A"));
+
+    let unchanged = network
+        .edges
+        .iter()
+        .find(|edge| edge.source_id == "C")
+        .unwrap();
+    assert!(!unchanged.orientation_swapped);
+    assert_eq!(unchanged.original_orientation(), ("C", "D"));
+}
+
+#[test]
+fn test_threshold_sweep() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV_THRESHOLD, 0.05, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let sweep = network.threshold_sweep(&[0.01, 0.02, 0.03, 0.05]);
+    assert_eq!(sweep.len(), 4);
+
+    // More nodes are clustered at looser thresholds
+    for window in sweep.windows(2) {
+        assert!(
+            window[1].1 >= window[0].1,
+            "Clustered nodes should not decrease: {:?}",
+            sweep
+        );
+    }
+
+    assert_eq!(sweep[0], (0.01, 2, 1));
+    assert_eq!(sweep[2], (0.03, 4, 1));
+    assert_eq!(sweep[3], (0.05, 8, 3));
+}
+
+#[test]
+fn test_compute_clusters_without_adjacency() {
+    // Clustering straight after a read rebuilds the adjacency first
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_clusters();
+
+    let summary = network.cluster_size_summary();
+    assert_eq!(summary.cluster_count, 3);
+    assert_eq!(summary.max_size, 4);
+    assert!(network.same_cluster("ID3", "ID4"));
+    assert_eq!(network.adjacency["ID1"].len(), 2);
+    assert_eq!(network.get_node("ID1").unwrap().degree, 2);
+
+    // ...including after a fast read, which leaves the adjacency empty
+    let mut fast = TransmissionNetwork::new();
+    fast.read_from_csv_str_fast(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    fast.compute_clusters();
+    assert_eq!(fast.cluster_assignment().len(), 8);
+    assert_eq!(fast.cluster_size_summary(), summary);
+}
+
+#[test]
+fn test_adjacency_json() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+
+    let adjacency: HashMap<String, Vec<String>> =
+        serde_json::from_str(&network.to_adjacency_json().unwrap()).unwrap();
+
+    assert_eq!(adjacency["ID1"], vec!["ID2", "ID3"]);
+    assert_eq!(adjacency["ID4"], vec!["ID2"]);
+    assert_eq!(adjacency.len(), 8);
+}
+
+#[test]
+fn test_merge_events() {
+    // Two dyads at 0.01, joined by a 0.02 link; E joins a dyad at 0.02 too
+    let csv = "A,B,0.01\nC,D,0.01\nB,C,0.02\nD,E,0.02\n";
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // Loosening merges the two dyads once; the singleton joining doesn't count
+    assert_eq!(network.merge_events(0.015, 0.03), 1);
+
+    // Nothing merges without loosening
+    assert_eq!(network.merge_events(0.015, 0.015), 0);
+    assert_eq!(network.merge_events(0.03, 0.015), 0);
+}
+
+#[test]
+fn test_clusters_ndjson() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let ndjson = network.to_clusters_ndjson();
+    let lines: Vec<&str> = ndjson.lines().collect();
+    assert_eq!(lines.len(), 3, "Should have one line per real cluster");
+
+    let mut sizes = Vec::new();
+    for line in lines {
+        let cluster: serde_json::Value =
+            serde_json::from_str(line).expect("Each line should be valid JSON");
+        assert!(cluster["cluster_id"].is_number());
+        assert_eq!(
+            cluster["nodes"].as_array().unwrap().len() as u64,
+            cluster["size"].as_u64().unwrap()
+        );
+        sizes.push(cluster["size"].as_u64().unwrap());
+    }
+    sizes.sort();
+    assert_eq!(sizes, vec![2, 2, 4]);
+}
+
+#[test]
+fn test_edge_weights() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    assert!(network.to_json().trace_results.edges.weight.is_none());
+
+    network.include_edge_weights = true;
+    let json = network.to_json();
+    let edges = &json.trace_results.edges;
+    let weights = edges.weight.as_ref().expect("Weights should be emitted");
+    assert_eq!(weights.len(), edges.length.len());
+
+    let idx = edges
+        .length
+        .iter()
+        .position(|&length| length == 0.01)
+        .unwrap();
+    assert!(
+        (weights[idx] - 0.99).abs() < 1e-9,
+        "0.01 distance should weigh 0.99"
+    );
+}
+
+#[test]
+fn test_rename_nodes() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+
+    let mapping = HashMap::from([("ID1".to_string(), "A".to_string())]);
+    network.rename_nodes(&mapping, false).unwrap();
+    network.compute_clusters();
+
+    assert!(network.get_node("ID1").is_none(), "Old ID should be gone");
+    assert_eq!(network.get_node("A").unwrap().degree, 2);
+    assert!(network
+        .edge_lookup
+        .contains_key(&("A".to_string(), "ID2".to_string())));
+    assert!(network
+        .edge_lookup
+        .contains_key(&("A".to_string(), "ID3".to_string())));
+    assert!(network.adjacency["ID2"].contains(&"A".to_string()));
+    assert_eq!(network.get_edge_count(), 5);
+
+    // Mapping two nodes onto one ID errors unless merging is requested
+    let mapping = HashMap::from([("ID7".to_string(), "ID8".to_string())]);
+    assert!(network.rename_nodes(&mapping, false).is_err());
+    network.rename_nodes(&mapping, true).unwrap();
+    assert_eq!(network.get_node_count(), 7);
+    assert_eq!(network.get_edge_count(), 4, "Merged edge should be dropped");
+}
+
+#[test]
+fn test_preserve_node_order() {
+    let csv = "Z9,B2,0.01\nM5,A1,0.01\nB2,A1,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network.preserve_node_order = true;
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let json = network.to_json();
+    let nodes = &json.trace_results.nodes;
+    assert_eq!(nodes.id, vec!["Z9", "B2", "M5", "A1"]);
+
+    // Edge indices follow the emitted node order
+    let edges = &json.trace_results.edges;
+    for (i, pair) in edges.sequences.iter().enumerate() {
+        assert_eq!(nodes.id[edges.source[i]], pair[0]);
+        assert_eq!(nodes.id[edges.target[i]], pair[1]);
+    }
+
+    // Sorted remains the default
+    network.preserve_node_order = false;
+    assert_eq!(
+        network.to_json().trace_results.nodes.id,
+        vec!["A1", "B2", "M5", "Z9"]
+    );
+}
+
+#[test]
+fn test_k_core() {
+    // A 4-clique (each degree 3) plus a pendant node attached to A
+    let csv = "A,B,0.01\nA,C,0.01\nA,D,0.01\nB,C,0.01\nB,D,0.01\nC,D,0.01\nA,P,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+
+    let core: HashSet<String> = ["A", "B", "C", "D"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(network.k_core(2), core, "2-core should exclude the pendant");
+    assert_eq!(network.k_core(3), core);
+    assert!(network.k_core(4).is_empty());
+    assert_eq!(network.k_core(1).len(), 5);
+}
+
+#[test]
+fn test_natural_edge_ordering() {
+    // Lexical ordering (default) puts N10 before N9
+    let edge = Edge::new("N10".to_string(), "N9".to_string(), None, None, 0.01).unwrap();
+    assert_eq!(edge.get_key(), ("N10".to_string(), "N9".to_string()));
+
+    let edge = Edge::new_with_ordering(
+        "N10".to_string(),
+        "N9".to_string(),
+        None,
+        None,
+        0.01,
+        IdOrdering::Natural,
+    )
+    .unwrap();
+    assert_eq!(edge.get_key(), ("N9".to_string(), "N10".to_string()));
+    assert!(edge.orientation_swapped);
+
+    // The network uses the configured ordering for its lookup keys
+    let mut network = TransmissionNetwork::new();
+    network.id_ordering = IdOrdering::Natural;
+    network
+        .read_from_csv_str("N10,N9,0.01\n", 0.03, InputFormat::Plain)
+        .unwrap();
+    assert!(network
+        .edge_lookup
+        .contains_key(&("N9".to_string(), "N10".to_string())));
+}
+
+#[test]
+fn test_edge_betweenness() {
+    // Two triangles joined by a single edge (A3-B1)
+    let csv =
+        "A1,A2,0.01\nA2,A3,0.01\nA1,A3,0.01\nA3,B1,0.01\nB1,B2,0.01\nB2,B3,0.01\nB1,B3,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+
+    let betweenness = network.edge_betweenness();
+    assert_eq!(betweenness.len(), 7, "Every edge should be scored");
+
+    let (top_edge, top_value) = betweenness
+        .iter()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        .unwrap();
+    assert_eq!(top_edge, &("A3".to_string(), "B1".to_string()));
+    // All 3 x 3 cross-triangle paths use the joining edge
+    assert!((top_value - 9.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_girvan_newman() {
+    // Barbell: two 4-cliques joined by a single edge (A4-B1)
+    let mut csv = String::new();
+    for group in ["A", "B"] {
+        for i in 1..=4 {
+            for j in i + 1..=4 {
+                csv.push_str(&format!("{}{},{}{},0.01\n", group, i, group, j));
+            }
+        }
+    }
+    csv.push_str("A4,B1,0.01\n");
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(&csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let cluster_id = network.get_node("A1").unwrap().cluster_id.unwrap();
+    let communities = network.girvan_newman(cluster_id, 2);
+    assert_eq!(
+        communities,
+        vec![vec!["A1", "A2", "A3", "A4"], vec!["B1", "B2", "B3", "B4"]]
+    );
+
+    // A target of 1 leaves the cluster intact
+    assert_eq!(network.girvan_newman(cluster_id, 1).len(), 1);
+}
+
+#[test]
+fn test_to_csr() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+
+    let (row_pointers, column_indices) = network.to_csr();
+
+    // Nodes are sorted: ID1..ID8 map to indices 0..7
+    assert_eq!(row_pointers, vec![0, 2, 4, 5, 6, 7, 8, 9, 10]);
+    assert_eq!(column_indices, vec![1, 2, 0, 3, 0, 1, 5, 4, 7, 6]);
+
+    let neighbors = |i: usize| &column_indices[row_pointers[i]..row_pointers[i + 1]];
+    assert_eq!(neighbors(0), &[1, 2]); // ID1 -> ID2, ID3
+    assert_eq!(neighbors(3), &[1]); // ID4 -> ID2
+}
+
+#[test]
+fn test_clear_and_reuse() {
+    let mut network = TransmissionNetwork::new();
+    network.zero_index_clusters = true;
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    network.clear();
+    assert_eq!(network.get_node_count(), 0);
+    assert_eq!(network.get_edge_count(), 0);
+    assert!(network.adjacency.is_empty());
+    assert!(network.edge_lookup.is_empty());
+    assert!(network.metadata.is_empty());
+    assert!(!network.zero_index_clusters);
+
+    // The cleared network can be rebuilt from scratch
+    network
+        .read_from_csv_str(TEST_CSV_THRESHOLD, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    assert_eq!(network.get_edge_count(), 3);
+    assert_eq!(network.get_node_count(), 8);
+}
+
+#[test]
+fn test_cluster_naming_prefix() {
+    let mut network = TransmissionNetwork::new();
+    network.cluster_naming = Some(ClusterNaming::Prefix("Cluster-".to_string()));
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let names = network.cluster_names();
+    let name_of = |id: &str| names[&network.get_node(id).unwrap().cluster_id.unwrap()].clone();
+
+    // The 4-node cluster is largest; the two pairs follow in cluster ID order
+    assert_eq!(name_of("ID1"), "Cluster-001");
+    let mut pair_names = vec![name_of("ID5"), name_of("ID7")];
+    pair_names.sort();
+    assert_eq!(pair_names, vec!["Cluster-002", "Cluster-003"]);
+
+    let json = serde_json::to_value(network.to_json()).unwrap();
+    let json_names = json["trace_results"]["cluster_names"].as_object().unwrap();
+    assert_eq!(json_names.len(), 3);
+    assert!(json_names.values().any(|name| name == "Cluster-001"));
+}
+
+#[test]
+fn test_max_edges_guard() {
+    let mut network = TransmissionNetwork::new();
+    network.max_edges = Some(2);
+
+    let result = network.read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain);
+    match result {
+        Err(NetworkError::SizeLimit(message)) => assert!(message.contains("2 edges")),
+        other => panic!("Expected a size limit error, got {:?}", other),
+    }
+
+    // The guard fires during parsing, before anything is materialized
+    assert_eq!(network.get_node_count(), 0);
+
+    let mut small = TransmissionNetwork::new();
+    small.max_edges = Some(2);
+    small
+        .read_from_csv_str("A,B,0.01\nB,C,0.01\n", 0.03, InputFormat::Plain)
+        .unwrap();
+    assert_eq!(small.get_edge_count(), 2);
+}
+
+#[test]
+fn test_collapse_multi_sample_subjects() {
+    let csv = "P1~s1,P1~s2,0.001\nP1~s1,P2~s1,0.02\nP1~s2,P2~s1,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network.collapse_by = Some("~".to_string());
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // Samples fold into two subject nodes, linked once at the minimum distance
+    assert_eq!(network.get_node_count(), 2);
+    assert!(network.get_node("P1").is_some());
+    assert!(network.get_node("P2").is_some());
+    assert_eq!(network.get_edge_count(), 1);
+    assert_eq!(
+        network.edges[0].get_key(),
+        ("P1".to_string(), "P2".to_string())
+    );
+    assert_eq!(network.edges[0].distance, 0.01);
+    assert!(network.inconsistent_edges().is_empty());
+}
+
+#[test]
+fn test_cluster_newick() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let newick = network.to_cluster_newick();
+    let lines: Vec<&str> = newick.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines.contains(&"(ID1,ID2,ID3,ID4);"));
+    assert!(lines.contains(&"(ID5,ID6);"));
+    assert!(lines.contains(&"(ID7,ID8);"));
+}
+
+#[test]
+fn test_dyads() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    assert_eq!(
+        network.dyads(),
+        vec![
+            ("ID5".to_string(), "ID6".to_string()),
+            ("ID7".to_string(), "ID8".to_string())
+        ]
+    );
+    assert_eq!(network.to_json().trace_results.network_summary.dyads, 2);
+}
+
+#[test]
+fn test_write_json_to_writer() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(TEST_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let mut buffer: Vec<u8> = Vec::new();
+    network.write_json(&mut buffer).unwrap();
+
+    let streamed: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    let mut expected = serde_json::to_value(network.to_json()).unwrap();
+
+    // Only the creation timestamp may differ between the two calls
+    expected["trace_results"]["Settings"]["created"] =
+        streamed["trace_results"]["Settings"]["created"].clone();
+    assert_eq!(streamed, expected);
+}
+
+#[test]
+fn test_degree_preserving_shuffle() {
+    // Two 3-node paths and two pairs
+    let csv = "A1,A2,0.01\nA2,A3,0.01\nB1,B2,0.01\nB2,B3,0.01\nC1,C2,0.01\nD1,D2,0.01\n";
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let shuffled = network.degree_preserving_shuffle(42, 20);
+
+    // Every node keeps its degree
+    for node in network.iter_nodes() {
+        assert_eq!(shuffled.get_node(&node.id).unwrap().degree, node.degree);
+    }
+    assert_eq!(shuffled.get_edge_count(), network.get_edge_count());
+
+    // No self-loops or multi-edges
+    let keys: HashSet<(String, String)> = shuffled.edges.iter().map(|e| e.get_key()).collect();
+    assert_eq!(keys.len(), shuffled.edges.len());
+    assert!(shuffled.edges.iter().all(|e| e.source_id != e.target_id));
+
+    // The wiring changed
+    let original: HashSet<(String, String)> = network.edges.iter().map(|e| e.get_key()).collect();
+    assert_ne!(keys, original);
+
+    // Shuffling is reproducible for a seed
+    let again = network.degree_preserving_shuffle(42, 20);
+    let again_keys: HashSet<(String, String)> = again.edges.iter().map(|e| e.get_key()).collect();
+    assert_eq!(again_keys, keys);
+}
+
+#[test]
+fn test_from_edges_matches_csv() {
+    let edges: Vec<(String, String, f64)> = TEST_CSV
+        .lines()
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            (
+                fields[0].to_string(),
+                fields[1].to_string(),
+                fields[2].parse().unwrap(),
+            )
+        })
+        .collect();
+
+    let mut from_vec = TransmissionNetwork::from_edges(&edges, 0.025).unwrap();
+    let mut from_csv = TransmissionNetwork::new();
+    from_csv
+        .read_from_csv_str(TEST_CSV, 0.025, InputFormat::Plain)
+        .unwrap();
+
+    for network in [&mut from_vec, &mut from_csv] {
+        network.compute_adjacency();
+        network.compute_clusters();
+    }
+
+    // ID5-ID6 (0.03) is above the threshold in both builds but its nodes remain
+    assert_eq!(from_vec.get_node_count(), from_csv.get_node_count());
+    assert_eq!(from_vec.get_edge_count(), 4);
+    let keys = |n: &TransmissionNetwork| -> HashSet<(String, String)> {
+        n.edges.iter().map(|e| e.get_key()).collect()
+    };
+    assert_eq!(keys(&from_vec), keys(&from_csv));
+
+    // Cluster IDs are assigned in sorted node order, connected nodes first
+    assert_eq!(from_vec.cluster_assignment(), from_csv.cluster_assignment());
+    let expected: HashMap<String, usize> = [
+        ("ID1", 0),
+        ("ID2", 0),
+        ("ID3", 0),
+        ("ID4", 0),
+        ("ID7", 1),
+        ("ID8", 1),
+        ("ID5", 2),
+        ("ID6", 3),
+    ]
+    .into_iter()
+    .map(|(id, cluster)| (id.to_string(), cluster))
+    .collect();
+    assert_eq!(from_vec.cluster_assignment(), expected);
+
+    // Self-loops are rejected the same way
+    let self_loop = vec![("A".to_string(), "A".to_string(), 0.01)];
+    assert!(matches!(
+        TransmissionNetwork::from_edges(&self_loop, 0.03),
+        Err(NetworkError::SelfLoop)
+    ));
+}
+
+#[test]
+fn test_capped_degree_distribution() {
+    // A hub linked to 100 leaves
+    let csv: String = (0..100).map(|i| format!("HUB,L{},0.01\n", i)).collect();
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(&csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let full = network.to_json().trace_results.degrees.Distribution;
+    assert_eq!(full.len(), 101);
+
+    network.max_reported_degree = Some(5);
+    let capped = network.to_json().trace_results.degrees.Distribution;
+    assert_eq!(capped, vec![0, 100, 0, 0, 0, 1]);
+}
+
+#[test]
+fn test_nodes_as_objects_matches_parallel_arrays() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str("A,B,0.01\nB,C,0.01\nD,E,0.01\n", 0.02, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let parallel = network.to_json().trace_results.nodes;
+    let objects = network.nodes_as_objects();
+    assert_eq!(objects.len(), parallel.id.len());
+    for (i, node) in objects.iter().enumerate() {
+        assert_eq!(node["id"], parallel.id[i]);
+        assert_eq!(node["cluster"], parallel.cluster[i]);
+        assert_eq!(node["patient_attributes"], parallel.patient_attributes[i]);
+        assert_eq!(
+            node["degree"],
+            network.get_node(&parallel.id[i]).unwrap().degree
+        );
+    }
+
+    // The output option swaps the Nodes representation
+    network.nodes_as_objects_output = true;
+    let json: serde_json::Value = serde_json::from_str(&network.to_json_string().unwrap()).unwrap();
+    assert_eq!(json["trace_results"]["Nodes"], serde_json::json!(objects));
+}
+
+#[test]
+fn test_modularity_of_disconnected_triangles() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(
+            "A,B,0.01\nB,C,0.01\nA,C,0.01\nD,E,0.01\nE,F,0.01\nD,F,0.01\n",
+            0.02,
+            InputFormat::Plain,
+        )
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // Two equal, fully separated communities reach the maximum Q = 1 - 1/2
+    let q = network.modularity();
+    assert!(q > 0.0);
+    assert!((q - 0.5).abs() < 1e-9, "unexpected modularity {}", q);
+}
+
+#[test]
+fn test_ego_network_radius_one() {
+    // Star centered on H, with a second ring hanging off leaf L1
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(
+            "H,L1,0.01\nH,L2,0.01\nH,L3,0.01\nL1,R1,0.01\n",
+            0.02,
+            InputFormat::Plain,
+        )
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let ego = network.ego_network("H", 1);
+    let mut ids: Vec<String> = ego.nodes.keys().cloned().collect();
+    ids.sort();
+    assert_eq!(ids, vec!["H", "L1", "L2", "L3"]);
+    assert_eq!(ego.get_edge_count(), 3);
+    assert_eq!(ego.get_node("H").unwrap().degree, 3);
+    assert_eq!(ego.get_node("L1").unwrap().degree, 1);
+
+    // Radius 2 reaches the second ring; an unknown center yields nothing
+    assert!(network.ego_network("H", 2).get_node("R1").is_some());
+    assert_eq!(network.ego_network("missing", 1).get_node_count(), 0);
+}
+
+#[test]
+fn test_degree_distribution_after_hiding_edge() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str("A,B,0.01\nB,C,0.01\n", 0.02, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    assert_eq!(
+        network.to_json().trace_results.degrees.Distribution,
+        vec![0, 2, 1]
+    );
+
+    // Hide B-C without touching the stored node degrees
+    let index = network
+        .edges
+        .iter()
+        .position(|e| e.get_key() == ("B".to_string(), "C".to_string()))
+        .unwrap();
+    network.edges[index].visible = false;
+
+    // A and B now have degree 1, C is isolated
+    assert_eq!(
+        network.to_json().trace_results.degrees.Distribution,
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn test_degree_outputs_after_hiding_edge() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str("A,B,0.01\nB,C,0.01\n", 0.02, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let index = network
+        .edges
+        .iter()
+        .position(|e| e.get_key() == ("B".to_string(), "C".to_string()))
+        .unwrap();
+    network.edges[index].visible = false;
+
+    // Outputs agree with the degree distribution even before recomputing
+    let check_outputs = |network: &TransmissionNetwork| {
+        let json = network.to_json();
+        assert_eq!(json.trace_results.degrees.Distribution, vec![1, 2]);
+        assert_eq!(json.trace_results.network_summary.Singletons, 1);
+        assert_eq!(json.trace_results.network_summary.sequences_used, 2);
+
+        let objects = network.nodes_as_objects();
+        let degrees: Vec<u64> = objects
+            .iter()
+            .map(|n| n["degree"].as_u64().unwrap())
+            .collect();
+        assert_eq!(degrees, vec![1, 1, 0]);
+
+        let cytoscape: serde_json::Value =
+            serde_json::from_str(&network.to_cytoscape_json().unwrap()).unwrap();
+        assert_eq!(cytoscape["elements"]["nodes"][2]["data"]["degree"], 0);
+
+        let table = network.to_node_table_csv();
+        assert!(table
+            .lines()
+            .any(|line| line.starts_with("C,") && line.contains(",0,")));
+    };
+    check_outputs(&network);
+
+    // Recomputing brings the stored degrees and clusters in line
+    network.compute_adjacency();
+    network.compute_clusters();
+    check_outputs(&network);
+    assert_eq!(network.get_node("B").unwrap().degree, 1);
+    assert_eq!(network.get_node("C").unwrap().degree, 0);
+    assert!(!network.same_cluster("B", "C"));
+    assert_eq!(network.to_json().trace_results.network_summary.Clusters, 1);
+}
+
+#[test]
+fn test_from_edge_tuples_json_matches_csv() {
+    let csv = "A,B,0.01\nB,A,0.02\nB,C,0.015\nD,E,0.5\n";
+    let json = r#"{"edges": [["A","B",0.01],["B","A",0.02],["B","C",0.015],["D","E",0.5]]}"#;
+
+    let mut from_json = TransmissionNetwork::from_edge_tuples_json(json, 0.03).unwrap();
+    let mut from_csv = TransmissionNetwork::new();
+    from_csv
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    for network in [&mut from_json, &mut from_csv] {
+        network.compute_adjacency();
+        network.compute_clusters();
+    }
+
+    assert_eq!(from_json.get_node_count(), from_csv.get_node_count());
+    assert_eq!(from_json.get_edge_count(), from_csv.get_edge_count());
+    let edge_set = |network: &TransmissionNetwork| -> Vec<(String, String, f64)> {
+        let mut edges: Vec<_> = network
+            .edges
+            .iter()
+            .filter(|e| e.visible)
+            .map(|e| (e.source_id.clone(), e.target_id.clone(), e.distance))
+            .collect();
+        edges.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        edges
+    };
+    assert_eq!(edge_set(&from_json), edge_set(&from_csv));
+
+    // Self-loops are rejected as in CSV input, and malformed tuples are errors
+    let self_loop = r#"{"edges": [["C","C",0.0]]}"#;
+    assert!(TransmissionNetwork::from_edge_tuples_json(self_loop, 0.03).is_err());
+    assert!(TransmissionNetwork::new()
+        .read_from_csv_str("C,C,0.0\n", 0.03, InputFormat::Plain)
+        .is_err());
+    assert!(TransmissionNetwork::from_edge_tuples_json(r#"{"edges": [["A"]]}"#, 0.03).is_err());
+}
+
+#[test]
+fn test_edges_below_is_monotonic() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(
+            "A,B,0.005\nB,C,0.01\nC,D,0.02\nD,E,0.03\nE,F,0.045\n",
+            0.05,
+            InputFormat::Plain,
+        )
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // Hidden edges still count towards the preview
+    network.edges[0].visible = false;
+
+    let counts: Vec<usize> = [0.0, 0.005, 0.015, 0.025, 0.04, 0.05]
+        .iter()
+        .map(|&threshold| network.edges_below(threshold))
+        .collect();
+    assert!(counts.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(counts, vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_reachable_from() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(
+            "ID1,ID2,0.005\nID2,ID3,0.02\nID3,ID4,0.005\nID5,ID6,0.01\n",
+            0.03,
+            InputFormat::Plain,
+        )
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // With the default cap (the threshold), the whole cluster is reachable
+    let reached = network.reachable_from("ID1", None);
+    let cluster: HashSet<String> = ["ID1", "ID2", "ID3", "ID4"]
+        .iter()
+        .map(|id| id.to_string())
+        .collect();
+    assert_eq!(reached, cluster);
+
+    // A tighter per-edge cap stops at the 0.02 link
+    let reached = network.reachable_from("ID1", Some(0.01));
+    let near: HashSet<String> = ["ID1", "ID2"].iter().map(|id| id.to_string()).collect();
+    assert_eq!(reached, near);
+
+    assert!(network.reachable_from("missing", None).is_empty());
+}
+
+#[test]
+fn test_suggest_id_merges() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(
+            "Patient01,Patient02,0.01\nPatient0l,Other,0.5\n",
+            0.03,
+            InputFormat::Plain,
+        )
+        .unwrap();
+
+    let suggestions = network.suggest_id_merges(1);
+    assert!(suggestions.contains(&("Patient01".to_string(), "Patient0l".to_string())));
+    assert!(!suggestions
+        .iter()
+        .any(|(a, b)| a == "Other" || b == "Other"));
+
+    // Nothing is merged, and a zero distance suggests nothing
+    assert!(network.get_node("Patient0l").is_some());
+    assert!(network.suggest_id_merges(0).is_empty());
+}
+
+#[test]
+fn test_membership_confidence() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(
+            "A,B,0.001\nC,D,0.0148\nE,F,0.5\n",
+            0.015,
+            InputFormat::Plain,
+        )
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let confidence = network.membership_confidence(0.005);
+    assert!(confidence["C"] < confidence["A"]);
+    assert_eq!(confidence["A"], 1.0);
+    assert!(confidence["C"] > 0.0 && confidence["C"] < 0.1);
+
+    // Singletons are not clustered at all
+    assert_eq!(confidence["E"], 0.0);
+    assert!(confidence.values().all(|value| (0.0..=1.0).contains(value)));
+}
+
+#[test]
+fn test_structure_hash_is_order_independent() {
+    let build = |csv: &str| {
+        let mut network = TransmissionNetwork::new();
+        network
+            .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+            .unwrap();
+        network.compute_adjacency();
+        network.compute_clusters();
+        network
+    };
+
+    let original = build("A,B,0.01\nB,C,0.02\nD,E,0.015\nF,G,0.5\n");
+    let shuffled = build("F,G,0.5\nE,D,0.015\nC,B,0.02\nA,B,0.01\n");
+    assert_eq!(original.structure_hash(), shuffled.structure_hash());
+
+    let changed = build("A,B,0.01\nB,C,0.025\nD,E,0.015\nF,G,0.5\n");
+    assert_ne!(original.structure_hash(), changed.structure_hash());
+}
+
+#[test]
+fn test_validate_and_repair_invariants() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str("A,B,0.01\nB,C,0.01\nD,E,0.01\n", 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    assert_eq!(network.validate_invariants(), Ok(()));
+
+    // Hide an edge without updating derived state, and corrupt a lookup index
+    let index = network
+        .edges
+        .iter()
+        .position(|e| e.get_key() == ("B".to_string(), "C".to_string()))
+        .unwrap();
+    network.edges[index].visible = false;
+    let key = ("D".to_string(), "E".to_string());
+    network.edge_lookup.insert(key, 99);
+    network.nodes.get_mut("A").unwrap().degree = 5;
+
+    let violations = network.validate_invariants().unwrap_err();
+    assert!(violations.iter().any(|v| v.contains("edge_lookup")));
+    assert!(violations.iter().any(|v| v.contains("adjacency of B")));
+    assert!(violations.iter().any(|v| v.contains("degree of A")));
+
+    network.repair();
+    assert_eq!(network.validate_invariants(), Ok(()));
+    assert_eq!(network.get_node("C").unwrap().degree, 0);
+    assert!(!network.same_cluster("B", "C"));
+}
+
+#[test]
+fn test_filtered_edges() {
+    let csv = "A,B,0.01,0.9\nB,C,0.05,0.9\nC,D,0.0001,0.9\nD,E,0.01,0.2\n";
+
+    let mut network = TransmissionNetwork::new();
+    network.retain_filtered_edges = true;
+    network.min_threshold = Some(0.001);
+    network.support_cutoff = Some(0.5);
+    network
+        .read_from_csv_str(csv, 0.015, InputFormat::Plain)
+        .unwrap();
+
+    let filtered = network.filtered_edges();
+    assert_eq!(
+        filtered,
+        vec![
+            (
+                "B".to_string(),
+                "C".to_string(),
+                0.05,
+                FilterReason::AboveThreshold
+            ),
+            (
+                "C".to_string(),
+                "D".to_string(),
+                0.0001,
+                FilterReason::BelowMinThreshold
+            ),
+            (
+                "D".to_string(),
+                "E".to_string(),
+                0.01,
+                FilterReason::LowSupport
+            ),
+        ]
+    );
+
+    // Without all-edges mode, read-time drops are not retained
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.015, InputFormat::Plain)
+        .unwrap();
+    assert!(network.filtered_edges().is_empty());
+}
+
+#[test]
+fn test_edge_try_new_drops_self_loops() {
+    let self_loop = Edge::try_new("A".to_string(), "A".to_string(), None, None, 0.01).unwrap();
+    assert!(self_loop.is_none());
+
+    let edge = Edge::try_new("B".to_string(), "A".to_string(), None, None, 0.01)
+        .unwrap()
+        .unwrap();
+    assert_eq!(edge.get_key(), ("A".to_string(), "B".to_string()));
+
+    // The erroring constructor is unchanged
+    assert!(matches!(
+        Edge::new("A".to_string(), "A".to_string(), None, None, 0.01),
+        Err(NetworkError::SelfLoop)
+    ));
+}
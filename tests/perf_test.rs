@@ -1,4 +1,5 @@
 use hivcluster_rs::{InputFormat, TransmissionNetwork};
+use std::collections::HashSet;
 use std::time::Instant;
 
 #[test]
@@ -77,3 +78,56 @@ fn test_performance() {
     // Verify that we have appropriate clustering
     assert!(clusters.len() > 0, "Should have created clusters");
 }
+
+#[test]
+fn test_fast_read_matches_normal_read() {
+    let mut csv_data = String::new();
+    for i in 1..500 {
+        for j in 1..=3 {
+            let distance = 0.005 * j as f64 + (i % 7) as f64 * 0.001;
+            csv_data.push_str(&format!("N{:05},N{:05},{:.6}\n", i, i + j * 7, distance));
+        }
+    }
+
+    let mut normal = TransmissionNetwork::new();
+    let start = Instant::now();
+    normal
+        .read_from_csv_str(&csv_data, 0.015, InputFormat::Plain)
+        .unwrap();
+    normal.compute_adjacency();
+    let normal_time = start.elapsed();
+
+    let mut fast = TransmissionNetwork::new();
+    let start = Instant::now();
+    fast.read_from_csv_str_fast(&csv_data, 0.015, InputFormat::Plain)
+        .unwrap();
+    fast.compute_adjacency();
+    let fast_time = start.elapsed();
+    println!("Normal read {:?}, fast read {:?}", normal_time, fast_time);
+
+    normal.compute_clusters();
+    fast.compute_clusters();
+
+    assert_eq!(fast.get_node_count(), normal.get_node_count());
+    assert_eq!(fast.get_edge_count(), normal.get_edge_count());
+    for (id, node) in &normal.nodes {
+        assert_eq!(
+            fast.get_node(id).unwrap().degree,
+            node.degree,
+            "degree of {}",
+            id
+        );
+    }
+
+    let partition = |network: &TransmissionNetwork| -> HashSet<Vec<String>> {
+        network
+            .retrieve_clusters(true)
+            .into_values()
+            .map(|mut nodes| {
+                nodes.sort();
+                nodes
+            })
+            .collect()
+    };
+    assert_eq!(partition(&fast), partition(&normal));
+}
@@ -100,3 +100,127 @@ fn test_singleton_detection() {
         );
     }
 }
+
+#[test]
+fn test_zero_node_network() {
+    // A header-only CSV produces a network with no nodes at all
+    let mut network = TransmissionNetwork::new();
+    let result = network.read_from_csv_str("source,target,distance\n", 0.015, InputFormat::Plain);
+    assert!(result.is_ok());
+
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let json = network.to_json();
+    let summary = &json.trace_results.network_summary;
+    assert_eq!(summary.Nodes, 0);
+    assert_eq!(summary.Edges, 0);
+    assert_eq!(summary.Clusters, 0);
+    assert_eq!(summary.Singletons, 0);
+    assert!(json.trace_results.cluster_sizes.is_empty());
+    assert!(network.to_json_string().is_ok());
+
+    let stats = network.get_network_stats();
+    assert_eq!(stats["largest_cluster"], 0);
+    assert_eq!(stats["mean_edge_distance"], 0.0);
+    assert_eq!(network.giant_component_fraction(), 0.0);
+    assert!(network.to_clusters_ndjson().is_empty());
+}
+
+#[test]
+fn test_threshold_excludes_everything() {
+    let mut network = TransmissionNetwork::new();
+    let result = network.read_from_csv_str(SINGLETON_CSV, 0.001, InputFormat::Plain);
+    assert!(result.is_ok());
+
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    assert_eq!(network.extract_singleton_nodes().len(), 10);
+
+    let json = network.to_json();
+    let summary = &json.trace_results.network_summary;
+    assert_eq!(summary.Nodes, 10);
+    assert_eq!(summary.Edges, 0);
+    assert_eq!(summary.Clusters, 0);
+    assert_eq!(summary.Singletons, 10);
+    assert_eq!(summary.sequences_used, 0);
+    assert!(json.trace_results.cluster_sizes.is_empty());
+    assert_eq!(json.trace_results.degrees.Distribution, vec![10]);
+
+    let stats = network.get_network_stats();
+    assert_eq!(stats["clusters"], 0);
+    assert_eq!(stats["largest_cluster"], 0);
+    assert_eq!(stats["max_edge_distance"], 0.0);
+}
+
+#[test]
+fn test_blank_ids_never_create_nodes() {
+    // A quoted-empty ID (with a leading space, so the CSV reader keeps the
+    // quotes), a whitespace-only quoted ID, and an empty quoted ID
+    let csv = "A, \"\",0.01\n\"   \",B,0.01\nC,\"\",0.01\nA,B,0.02\n";
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+
+    let mut ids: Vec<&str> = network.iter_nodes().map(|node| node.id.as_str()).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["A", "B"]);
+    assert_eq!(network.get_edge_count(), 1);
+}
+
+#[test]
+fn test_collapsed_singletons_share_cluster_zero() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(SINGLETON_CSV, 0.15, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+    network.collapse_singletons = true;
+
+    let json = network.to_json();
+    let nodes = &json.trace_results.nodes;
+    for (id, cluster) in nodes.id.iter().zip(nodes.cluster.iter()) {
+        if ["ID7", "ID8", "ID9", "ID10"].contains(&id.as_str()) {
+            assert_eq!(
+                *cluster, 0,
+                "{} should be in the singleton pseudo-cluster",
+                id
+            );
+        } else {
+            assert!(*cluster > 0, "{} should keep its own cluster", id);
+        }
+    }
+
+    // Singletons are still counted, and the mode is flagged in the settings
+    assert_eq!(json.trace_results.network_summary.Singletons, 4);
+    assert_eq!(json.trace_results.network_summary.Clusters, 3);
+    assert!(json.trace_results.settings.collapsed_singletons);
+}
+
+#[test]
+fn test_cluster_size_summary() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(SINGLETON_CSV, 0.15, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let summary = network.cluster_size_summary();
+    assert_eq!(summary.cluster_count, 3);
+    assert_eq!(summary.dyads, 3);
+    assert_eq!(summary.singletons, 4);
+    assert_eq!(summary.mean_size, 2.0);
+    assert_eq!(summary.median_size, 2.0);
+    assert_eq!(summary.max_size, 2);
+
+    // An empty network reports zeros
+    let empty = TransmissionNetwork::new().cluster_size_summary();
+    assert_eq!(empty.cluster_count, 0);
+    assert_eq!(empty.mean_size, 0.0);
+    assert_eq!(empty.singletons, 0);
+}
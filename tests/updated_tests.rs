@@ -1,4 +1,11 @@
-use hivcluster_rs::{InputFormat, TransmissionNetwork};
+use chrono::{TimeZone, Utc};
+use hivcluster_rs::{
+    build_network_with_warnings_internal, describe_vector, network_stats_internal, AehFieldSchema,
+    AttributeConflictPolicy, ColumnMapping, CsvLimits, DateBucket, IdNormalization, InputFormat,
+    ParseWarning, SequenceCountMode, ThresholdMode, TransmissionNetwork,
+};
+use std::collections::HashMap;
+use std::io::Read;
 
 // Test data with varying distances
 const BASIC_NETWORK_CSV: &str = r#"source,target,distance
@@ -328,3 +335,1121 @@ fn test_large_network_performance() {
     let clusters = network.retrieve_clusters(false);
     assert!(!clusters.is_empty(), "Should have identified clusters");
 }
+
+#[test]
+fn test_aeh_custom_date_position() {
+    // Date is in pipe-field 2; the standard date position is empty
+    let csv = "source,target,distance\npatient1||2020-01-15|ARV,patient2||2020-02-20|No ARV,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network.aeh_schema = AehFieldSchema {
+        date_index: 2,
+        field_names: HashMap::from([(3, "treatment".to_string())]),
+    };
+    let result = network.read_from_csv_str(csv, 0.03, InputFormat::AEH);
+    assert!(
+        result.is_ok(),
+        "Failed to parse AEH CSV: {:?}",
+        result.err()
+    );
+
+    let patient = network.get_node("patient1").expect("patient1 should exist");
+    let expected = Utc.with_ymd_and_hms(2020, 1, 15, 0, 0, 0).unwrap();
+    assert_eq!(patient.get_most_recent_date(), Some(expected));
+    assert_eq!(
+        patient.named_attributes.get("treatment"),
+        Some(&"ARV".to_string())
+    );
+}
+
+#[test]
+fn test_edge_distance_stats() {
+    let mut network = TransmissionNetwork::new();
+    let result = network.read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain);
+    assert!(result.is_ok());
+
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let stats = network.get_network_stats();
+
+    let min = stats.get("min_edge_distance").and_then(|v| v.as_f64());
+    let max = stats.get("max_edge_distance").and_then(|v| v.as_f64());
+    assert_eq!(min, Some(0.01), "Min edge distance should be 0.01");
+    assert_eq!(max, Some(0.03), "Max edge distance should be 0.03");
+
+    let median = stats.get("median_edge_distance").and_then(|v| v.as_f64());
+    assert_eq!(median, Some(0.02), "Median edge distance should be 0.02");
+    assert!(stats.contains_key("mean_edge_distance"));
+    assert!(stats.contains_key("total_edge_distance"));
+}
+
+#[test]
+fn test_network_stats_with_distances() {
+    let json = network_stats_internal(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain, true).unwrap();
+    let stats: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    let distances = stats["edge_distances"].as_array().unwrap();
+    assert_eq!(
+        distances.len(),
+        5,
+        "Should include every visible edge distance"
+    );
+    assert_eq!(stats["min_edge_distance"].as_f64(), Some(0.01));
+    assert_eq!(stats["max_edge_distance"].as_f64(), Some(0.03));
+
+    // Distances are only included on request
+    let json = network_stats_internal(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain, false).unwrap();
+    let stats: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert!(stats.get("edge_distances").is_none());
+}
+
+#[test]
+fn test_id_case_normalization() {
+    let csv = "source,target,distance\nID1,ID2,0.01\nid1,ID3,0.01\n";
+
+    // Default keeps case variants as distinct nodes
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    assert_eq!(network.get_node_count(), 4);
+
+    // Lowercasing merges ID1 and id1 into one node
+    let mut network = TransmissionNetwork::new();
+    network.id_normalization = IdNormalization::Lowercase;
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    assert_eq!(network.get_node_count(), 3, "ID1 and id1 should be merged");
+
+    let node = network.get_node("id1").expect("Merged node should exist");
+    assert_eq!(node.degree, 2, "Merged node should keep both edges");
+}
+
+#[test]
+fn test_direction_reasons() {
+    let csv = "source,target,distance
+a|2020-01-01,b|2020-02-01,0.01
+b|2020-02-01,c|2020-02-01,0.01
+c|2020-02-01,d,0.01
+e,f,0.01
+f,g,0.01
+";
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let json = network.to_json();
+    let directed = &json.trace_results.directed_edges;
+    assert_eq!(directed.Count, 1, "Only a-b has two distinct dates");
+    assert_eq!(directed.reasons["Equal dates"], 1);
+    assert_eq!(directed.reasons["One date missing"], 1);
+    assert_eq!(directed.reasons["Both dates missing"], 2);
+}
+
+#[test]
+fn test_giant_component_fraction() {
+    let mut network = TransmissionNetwork::new();
+    assert_eq!(network.giant_component_fraction(), 0.0);
+
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // 4-node cluster out of 7 connected nodes
+    let fraction = network.giant_component_fraction();
+    assert!(
+        (fraction - 4.0 / 7.0).abs() < 1e-9,
+        "Expected ~0.57, got {}",
+        fraction
+    );
+}
+
+#[test]
+fn test_sequences_used_definition() {
+    // p1 was sampled twice; p4-p5 are too far apart to link
+    let csv = "source,target,distance
+p1|2020-01-01,p2|2020-01-01,0.01
+p1|2020-03-01,p3,0.01
+p4,p5,0.5
+";
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let json = network.to_json();
+    assert_eq!(json.trace_results.network_summary.sequences_used, 3);
+    assert_eq!(
+        json.trace_results.settings.sequences_used,
+        "connected nodes"
+    );
+
+    network.sequence_count_mode = SequenceCountMode::DistinctSamples;
+    let json = network.to_json();
+    assert_eq!(
+        json.trace_results.network_summary.sequences_used, 4,
+        "p1 contributes two samples"
+    );
+    assert_eq!(
+        json.trace_results.settings.sequences_used,
+        "distinct samples"
+    );
+}
+
+#[test]
+fn test_suggest_threshold() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.05, InputFormat::Plain)
+        .unwrap();
+
+    let stats = network.get_network_stats();
+    let median = stats["median_edge_distance"].as_f64().unwrap();
+    assert!((network.suggest_threshold(50.0) - median).abs() < 1e-12);
+
+    assert_eq!(network.suggest_threshold(0.0), 0.01);
+    assert_eq!(network.suggest_threshold(100.0), 0.04);
+}
+
+#[test]
+fn test_inconsistent_duplicate_edges() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(DUPLICATE_EDGES_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+
+    let inconsistent = network.inconsistent_edges();
+    assert!(
+        inconsistent.contains(&("ID1".to_string(), "ID3".to_string(), 0.015, 0.01)),
+        "ID1-ID3 should be flagged: {:?}",
+        inconsistent
+    );
+    assert!(inconsistent.contains(&("ID1".to_string(), "ID2".to_string(), 0.01, 0.02)));
+    assert_eq!(inconsistent.len(), 2);
+}
+
+#[test]
+fn test_describe_vector_small_quartiles() {
+    let cases: [(&[f64], (f64, f64)); 4] = [
+        (&[5.0], (5.0, 5.0)),
+        (&[3.0, 1.0], (1.5, 2.5)),
+        (&[1.0, 2.0, 3.0], (1.5, 2.5)),
+        (&[4.0, 1.0, 3.0, 2.0], (1.75, 3.25)),
+    ];
+
+    for (values, (q1, q3)) in cases {
+        let description = describe_vector(values).unwrap();
+        assert!(
+            (description.iqr.0 - q1).abs() < 1e-12,
+            "q1 for {:?}",
+            values
+        );
+        assert!(
+            (description.iqr.1 - q3).abs() < 1e-12,
+            "q3 for {:?}",
+            values
+        );
+        assert!(description.min <= description.iqr.0);
+        assert!(description.iqr.0 <= description.iqr.1);
+        assert!(description.iqr.1 <= description.max);
+    }
+
+    assert!(describe_vector(&[]).is_none());
+}
+
+#[test]
+fn test_threshold_mode_at_boundary() {
+    let csv = "A,B,0.015\nB,C,0.01\n";
+
+    let mut inclusive = TransmissionNetwork::new();
+    inclusive
+        .read_from_csv_str(csv, 0.015, InputFormat::Plain)
+        .unwrap();
+    assert_eq!(inclusive.get_edge_count(), 2);
+
+    let mut exclusive = TransmissionNetwork::new();
+    exclusive.threshold_mode = ThresholdMode::Exclusive;
+    exclusive
+        .read_from_csv_str(csv, 0.015, InputFormat::Plain)
+        .unwrap();
+    assert_eq!(exclusive.get_edge_count(), 1);
+    assert_eq!(exclusive.get_node("A").unwrap().degree, 0);
+}
+
+#[test]
+fn test_recent_growth_clusters() {
+    let csv = "A|2019-03-01,B|2022-06-15,0.01\nC|2019-01-10,D|2019-08-20,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let growing_cluster = network.get_node("A").unwrap().cluster_id.unwrap();
+    let since = Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap();
+    assert_eq!(network.recent_growth_clusters(since), vec![growing_cluster]);
+
+    // Nothing is flagged once the window starts after the newest node
+    let later = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+    assert!(network.recent_growth_clusters(later).is_empty());
+}
+
+#[test]
+fn test_cluster_edge_counts() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let json = network.to_json();
+    let trace = &json.trace_results;
+
+    // The 4-node path ID1-ID4 has 3 edges; the ID6-ID8 path has 2
+    assert_eq!(trace.cluster_sizes, vec![3, 4]);
+    assert_eq!(trace.cluster_edge_counts, vec![2, 3]);
+}
+
+#[test]
+fn test_clusters_detailed() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let details = network.clusters_detailed();
+    assert_eq!(details.len(), 2);
+
+    let big = details.iter().max_by_key(|d| d.nodes.len()).unwrap();
+    assert_eq!(big.nodes, vec!["ID1", "ID2", "ID3", "ID4"]);
+    assert_eq!(big.edges.len(), 3);
+    assert!(big
+        .edges
+        .iter()
+        .all(|e| big.nodes.contains(&e.source_id) && big.nodes.contains(&e.target_id)));
+}
+
+#[test]
+fn test_cluster_triangle_count() {
+    let clique = "A,B,0.01\nA,C,0.01\nA,D,0.01\nB,C,0.01\nB,D,0.01\nC,D,0.01\n";
+    let path = "P1,P2,0.01\nP2,P3,0.01\nP3,P4,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(&format!("{}{}", clique, path), 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let stats = network.cluster_statistics();
+    assert_eq!(stats.len(), 2);
+
+    let clique_id = network.get_node("A").unwrap().cluster_id.unwrap();
+    let clique_stats = stats.iter().find(|s| s.id == clique_id).unwrap();
+    assert_eq!(clique_stats.size, 4);
+    assert_eq!(clique_stats.edge_count, 6);
+    assert_eq!(clique_stats.triangle_count, 4);
+
+    let path_stats = stats.iter().find(|s| s.id != clique_id).unwrap();
+    assert_eq!(path_stats.size, 4);
+    assert_eq!(path_stats.edge_count, 3);
+    assert_eq!(path_stats.triangle_count, 0);
+}
+
+#[test]
+fn test_plain_with_date_format() {
+    let csv = "patient123_2020-05-01,patient456,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::PlainWithDate)
+        .unwrap();
+
+    // The full string stays the ID, and the embedded date is extracted
+    let dated = network.get_node("patient123_2020-05-01").unwrap();
+    let expected = Utc.with_ymd_and_hms(2020, 5, 1, 0, 0, 0).unwrap();
+    assert_eq!(dated.get_most_recent_date(), Some(expected));
+    assert_eq!(
+        network
+            .get_node("patient456")
+            .unwrap()
+            .get_most_recent_date(),
+        None
+    );
+
+    // Pure Plain ignores the date
+    let mut plain = TransmissionNetwork::new();
+    plain
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    let node = plain.get_node("patient123_2020-05-01").unwrap();
+    assert_eq!(node.get_most_recent_date(), None);
+}
+
+#[test]
+fn test_node_table_csv() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(FORMATTED_IDS_CSV, 0.03, InputFormat::AEH)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let table = network.to_node_table_csv();
+    let mut lines = table.lines();
+    let header = lines.next().unwrap();
+    assert!(header.starts_with("id,cluster,degree,earliest_date,latest_date"));
+    assert!(header.ends_with(",field_2"));
+
+    let rows: Vec<&str> = lines.collect();
+    assert_eq!(rows.len(), network.get_node_count());
+    assert_eq!(rows[0], "patient1,1,1,2020-01-15,2020-01-15,ARV");
+}
+
+#[test]
+fn test_provenance_block() {
+    let mut network = TransmissionNetwork::new();
+    network.include_provenance = true;
+    network.input_file = Some("basic.csv".to_string());
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let json = serde_json::to_value(network.to_json()).unwrap();
+    let provenance = &json["trace_results"]["provenance"];
+    assert_eq!(provenance["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(provenance["threshold"], 0.03);
+    assert_eq!(provenance["input_file"], "basic.csv");
+    assert_eq!(provenance["format"], "Plain");
+    assert_eq!(provenance["input_rows"], 6);
+
+    // Without the flag there is no provenance block
+    network.include_provenance = false;
+    let json = serde_json::to_value(network.to_json()).unwrap();
+    assert!(json["trace_results"].get("provenance").is_none());
+}
+
+#[test]
+fn test_same_cluster() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    assert!(network.same_cluster("ID1", "ID4"));
+    assert!(!network.same_cluster("ID1", "ID5"));
+    assert!(!network.same_cluster("ID1", "missing"));
+}
+
+#[test]
+fn test_shortest_path() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // ID1-ID2-ID3-ID4 is a path at this threshold
+    assert_eq!(
+        network.shortest_path("ID1", "ID4"),
+        Some(vec![
+            "ID1".to_string(),
+            "ID2".to_string(),
+            "ID3".to_string(),
+            "ID4".to_string()
+        ])
+    );
+
+    let (_, distance) = network.shortest_path_with_distance("ID4", "ID1").unwrap();
+    assert!((distance - 0.06).abs() < 1e-12);
+
+    assert_eq!(network.shortest_path("ID1", "ID6"), None);
+    assert_eq!(network.shortest_path("ID1", "missing"), None);
+}
+
+#[test]
+fn test_edge_direction_output() {
+    // patient3 is sampled before patient2, and given first on its row
+    let csv = "patient1|2020-01-15,patient2|2020-02-20,0.01\n\
+               patient3|2020-01-01,patient2|2020-02-20,0.02\n\
+               patient1|2020-01-15,patient4|2020-01-15,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network.include_edge_direction = true;
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let edges = network.to_json().trace_results.edges;
+    let ids = ["patient1", "patient2", "patient3", "patient4"];
+    let pairs: Vec<(&str, &str)> = edges
+        .source
+        .iter()
+        .zip(&edges.target)
+        .map(|(&s, &t)| (ids[s], ids[t]))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("patient1", "patient2"),
+            ("patient2", "patient3"),
+            ("patient1", "patient4")
+        ]
+    );
+
+    // patient1 -> patient2 forward; patient3 -> patient2 points backwards;
+    // equal dates are unresolved
+    assert_eq!(edges.direction, Some(vec![1, -1, 0]));
+    assert_eq!(edges.orientation_swapped, Some(vec![false, true, false]));
+}
+
+#[test]
+fn test_csv_limits_reject_pathological_input() {
+    let limits = CsvLimits {
+        max_line_length: 1024,
+        max_field_length: 64,
+    };
+
+    // A single giant line
+    let long_line = format!("{},B,0.01\n", "A".repeat(10_000));
+    let mut network = TransmissionNetwork::new();
+    network.csv_limits = Some(limits);
+    let err = network
+        .read_from_csv_str(&long_line, 0.03, InputFormat::Plain)
+        .unwrap_err();
+    assert!(err.to_string().contains("Line 1"), "{}", err);
+
+    // A quoted field spanning many short lines
+    let runaway_quote = format!("A,\"{}\",0.01\n", "x\n".repeat(200));
+    let mut network = TransmissionNetwork::new();
+    network.csv_limits = Some(limits);
+    let err = network
+        .read_from_csv_str(&runaway_quote, 0.03, InputFormat::Plain)
+        .unwrap_err();
+    assert!(err.to_string().contains("limit is 64"), "{}", err);
+    assert_eq!(network.get_node_count(), 0);
+
+    // Ordinary input is unaffected
+    let mut network = TransmissionNetwork::new();
+    network.csv_limits = Some(limits);
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+}
+
+#[test]
+fn test_cross_attribute_edges() {
+    let csv = "A|2020-01-01|US,B|2020-01-01|US,0.01\n\
+               B|2020-01-01|US,C|2020-01-01|Canada,0.01\n\
+               C|2020-01-01|Canada,D|2020-01-01|Canada,0.01\n";
+
+    let mut network = TransmissionNetwork::new();
+    network.aeh_schema.field_names = HashMap::from([(2, "country".to_string())]);
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+        .unwrap();
+
+    assert_eq!(
+        network.cross_attribute_edges("country"),
+        vec![(
+            "B".to_string(),
+            "C".to_string(),
+            "US".to_string(),
+            "Canada".to_string()
+        )]
+    );
+    assert!(network.cross_attribute_edges("missing").is_empty());
+}
+
+#[test]
+fn test_distance_rounding_in_output() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(
+            "A,B,0.015000000000000001\nB,C,0.0123456789\n",
+            0.02,
+            InputFormat::Plain,
+        )
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // Full precision by default
+    let lengths = network.to_json().trace_results.edges.length;
+    assert!(lengths.contains(&0.0123456789));
+
+    network.distance_significant_figures = Some(4);
+    let mut lengths = network.to_json().trace_results.edges.length;
+    lengths.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(lengths, vec![0.01235, 0.015]);
+
+    // Significant figures, not decimal places: small distances keep their digits
+    let mut small = TransmissionNetwork::new();
+    small
+        .read_from_csv_str("A,B,0.000123456\n", 0.03, InputFormat::Plain)
+        .unwrap();
+    small.distance_significant_figures = Some(3);
+    assert_eq!(small.to_json().trace_results.edges.length, vec![0.000123]);
+
+    // Stored distances keep their precision
+    assert!(network.edges.iter().any(|e| e.distance == 0.0123456789));
+}
+
+#[test]
+fn test_attribute_keys_from_lanl_ids() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(LANL_IDS_CSV, 0.03, InputFormat::LANL)
+        .unwrap();
+
+    let keys = network.attribute_keys();
+    assert!(keys.contains(&"country".to_string()));
+    assert!(keys.contains(&"subtype".to_string()));
+
+    // Sorted and without duplicates
+    let mut expected = keys.clone();
+    expected.sort();
+    expected.dedup();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn test_attribute_conflict_policies() {
+    // P1 appears in two rows with different countries
+    let csv = "P1|2020-01-01|US,P2|2020-01-01|US,0.01\nP3|2020-01-01|CA,P1|2020-01-01|MX,0.01\n";
+
+    let build = |policy: AttributeConflictPolicy| {
+        let mut network = TransmissionNetwork::new();
+        network.aeh_schema.field_names = HashMap::from([(2, "country".to_string())]);
+        network.attribute_conflict_policy = policy;
+        network
+            .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+            .map(|_| network)
+    };
+    let country = |network: &TransmissionNetwork| {
+        network.get_node("P1").unwrap().named_attributes["country"].clone()
+    };
+
+    // The default keeps the last value
+    assert_eq!(
+        country(&build(AttributeConflictPolicy::default()).unwrap()),
+        "MX"
+    );
+    assert_eq!(
+        country(&build(AttributeConflictPolicy::LastWins).unwrap()),
+        "MX"
+    );
+    assert_eq!(
+        country(&build(AttributeConflictPolicy::FirstWins).unwrap()),
+        "US"
+    );
+    assert!(build(AttributeConflictPolicy::Error).is_err());
+
+    let collected = build(AttributeConflictPolicy::CollectAll).unwrap();
+    assert_eq!(country(&collected), "US");
+    assert_eq!(
+        collected.get_node("P1").unwrap().collected_attributes["country"],
+        vec!["US", "MX"]
+    );
+}
+
+#[test]
+fn test_rename_nodes_attribute_conflict() {
+    // P1 and P3 are merged into one node, with different countries
+    let csv = "P1|2020-01-01|US,P2|2020-01-01|US,0.01\nP3|2020-01-01|MX,P2|2020-01-01|US,0.01\n";
+    let mapping = HashMap::from([("P3".to_string(), "P1".to_string())]);
+
+    let rename = |policy: AttributeConflictPolicy| {
+        let mut network = TransmissionNetwork::new();
+        network.aeh_schema.field_names = HashMap::from([(2, "country".to_string())]);
+        network
+            .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+            .unwrap();
+        network.attribute_conflict_policy = policy;
+        let result = network.rename_nodes(&mapping, true);
+        (network, result)
+    };
+
+    // Nodes merge in ID order, so P3's value comes last
+    let (network, result) = rename(AttributeConflictPolicy::LastWins);
+    result.unwrap();
+    assert_eq!(
+        network.get_node("P1").unwrap().named_attributes["country"],
+        "MX"
+    );
+
+    let (network, result) = rename(AttributeConflictPolicy::FirstWins);
+    result.unwrap();
+    assert_eq!(
+        network.get_node("P1").unwrap().named_attributes["country"],
+        "US"
+    );
+
+    // A rejected merge leaves the network as it was
+    let (network, result) = rename(AttributeConflictPolicy::Error);
+    assert!(result.is_err());
+    assert!(network.get_node("P3").is_some());
+    assert_eq!(network.get_node_count(), 3);
+}
+
+#[test]
+fn test_cytoscape_json_export() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let output = network.to_cytoscape_json().unwrap();
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let nodes = json["elements"]["nodes"].as_array().unwrap();
+    let edges = json["elements"]["edges"].as_array().unwrap();
+    assert_eq!(nodes.len(), network.get_node_count());
+    assert_eq!(edges.len(), network.get_edge_count());
+
+    // Every edge refers to exported nodes and carries its distance
+    let ids: Vec<&str> = nodes
+        .iter()
+        .map(|node| node["data"]["id"].as_str().unwrap())
+        .collect();
+    for edge in edges {
+        assert!(ids.contains(&edge["data"]["source"].as_str().unwrap()));
+        assert!(ids.contains(&edge["data"]["target"].as_str().unwrap()));
+        assert!(edge["data"]["distance"].is_f64());
+    }
+    assert!(nodes[0]["data"]["cluster"].is_i64());
+    assert!(nodes[0]["data"]["degree"].is_u64());
+}
+
+#[test]
+fn test_gzip_json_round_trip() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(BASIC_NETWORK_CSV, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let compressed = network.write_json_gzip(Vec::new()).unwrap();
+    let mut decompressed = String::new();
+    flate2::read::GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut decompressed)
+        .unwrap();
+
+    // Pretty-printed, like the uncompressed output
+    assert!(decompressed.starts_with("{\n  \"trace_results\""));
+
+    let json: serde_json::Value = serde_json::from_str(&decompressed).unwrap();
+    let expected: serde_json::Value =
+        serde_json::from_str(&network.to_json_string().unwrap()).unwrap();
+    assert_eq!(
+        json["trace_results"]["Nodes"],
+        expected["trace_results"]["Nodes"]
+    );
+    assert_eq!(
+        json["trace_results"]["Edges"],
+        expected["trace_results"]["Edges"]
+    );
+}
+
+#[test]
+fn test_monthly_date_histogram() {
+    let csv = "P1|2020-01-05,P2|2020-01-20,0.01\nP3|2020-02-11,P4,0.01\nP5|2020-01-31,P3|2020-02-11,0.01\n";
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+        .unwrap();
+
+    // P4 has no date and is skipped
+    let monthly = network.date_histogram(DateBucket::Month);
+    assert_eq!(
+        monthly.into_iter().collect::<Vec<_>>(),
+        vec![("2020-01".to_string(), 3), ("2020-02".to_string(), 1)]
+    );
+
+    let weekly = network.date_histogram(DateBucket::IsoWeek);
+    assert_eq!(weekly["2020-W01"], 1);
+    assert_eq!(weekly.values().sum::<usize>(), 4);
+}
+
+#[test]
+fn test_min_threshold_band() {
+    let mut network = TransmissionNetwork::new();
+    network.min_threshold = Some(0.001);
+    network
+        .read_from_csv_str("A,B,0.0\nC,D,0.01\n", 0.015, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    // The identical pair is excluded (its nodes stay as singletons)
+    assert_eq!(network.get_edge_count(), 1);
+    assert_eq!(network.get_node("A").unwrap().degree, 0);
+    assert_eq!(network.get_node("C").unwrap().degree, 1);
+
+    let settings = network.to_json().trace_results.settings;
+    assert_eq!(
+        settings.edge_filtering.as_deref(),
+        Some("distance in [0.001, 0.015]")
+    );
+}
+
+#[test]
+fn test_annotate_csv_with_clusters() {
+    let csv = "source,target,distance\nB,A,0.01\nD,C,0.02\nA,C,0.5\n";
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let cluster = |id: &str| {
+        let json = network.to_json().trace_results.nodes;
+        let idx = json.id.iter().position(|node| node == id).unwrap();
+        json.cluster[idx].to_string()
+    };
+
+    let annotated = network
+        .annotate_csv_with_clusters(csv, InputFormat::Plain)
+        .unwrap();
+    let lines: Vec<&str> = annotated.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "source,target,distance,source_cluster,target_cluster".to_string(),
+            format!("B,A,0.01,{},{}", cluster("B"), cluster("A")),
+            format!("D,C,0.02,{},{}", cluster("D"), cluster("C")),
+            format!("A,C,0.5,{},{}", cluster("A"), cluster("C")),
+        ]
+    );
+    assert_ne!(cluster("A"), cluster("C"));
+}
+
+#[test]
+fn test_read_node_metadata_csv() {
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str("A,B,0.01\nB,C,0.01\n", 0.03, InputFormat::Plain)
+        .unwrap();
+
+    let metadata = "id,risk,country\nA,MSM,US\n B ,PWID,\nZ,HET,MX\n";
+    network.read_node_metadata_csv(metadata).unwrap();
+
+    let a = network.get_node("A").unwrap();
+    assert_eq!(
+        a.named_attributes.get("risk").map(String::as_str),
+        Some("MSM")
+    );
+    assert_eq!(
+        a.named_attributes.get("country").map(String::as_str),
+        Some("US")
+    );
+
+    // IDs are trimmed and empty values are skipped
+    let b = network.get_node("B").unwrap();
+    assert_eq!(
+        b.named_attributes.get("risk").map(String::as_str),
+        Some("PWID")
+    );
+    assert!(!b.named_attributes.contains_key("country"));
+
+    assert!(network.get_node("C").unwrap().named_attributes.is_empty());
+
+    // Sidecar rows for unknown IDs apply once those nodes are created
+    assert!(network.get_node("Z").is_none());
+    network
+        .read_from_csv_str("C,Z,0.01\n", 0.03, InputFormat::Plain)
+        .unwrap();
+    let z = network.get_node("Z").unwrap();
+    assert_eq!(
+        z.named_attributes.get("country").map(String::as_str),
+        Some("MX")
+    );
+
+    // A header without attribute columns is rejected
+    assert!(network.read_node_metadata_csv("id\nA\n").is_err());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_csv_parse_matches_serial() {
+    // Duplicate pairs in both orientations, with the smaller distance appearing
+    // before and after the larger one
+    let mut csv = String::from("source,target,distance\n");
+    for i in 0..2000 {
+        csv.push_str(&format!("N{},N{},0.0{}\n", i, i + 1, 1 + i % 3));
+        if i % 7 == 0 {
+            csv.push_str(&format!("N{},N{},0.001\n", i + 1, i));
+        }
+        if i % 11 == 0 {
+            csv.push_str(&format!("N{},N{},0.05\n", i, i + 2));
+        }
+    }
+
+    let edges = |network: &TransmissionNetwork| {
+        let mut edges: Vec<(String, String, f64)> = network
+            .edges
+            .iter()
+            .map(|e| (e.source_id.clone(), e.target_id.clone(), e.distance))
+            .collect();
+        edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        edges
+    };
+
+    let mut serial = TransmissionNetwork::new();
+    serial
+        .read_from_csv_str(&csv, 0.03, InputFormat::Plain)
+        .unwrap();
+    let mut parallel = TransmissionNetwork::new();
+    parallel
+        .read_from_csv_str_parallel(&csv, 0.03, InputFormat::Plain)
+        .unwrap();
+
+    assert_eq!(edges(&parallel), edges(&serial));
+    assert_eq!(parallel.get_node_count(), serial.get_node_count());
+    assert_eq!(parallel.inconsistent_edges(), serial.inconsistent_edges());
+
+    // Errors match too
+    let bad = "A,B,0.01\nC,D,oops\n";
+    let serial_err = TransmissionNetwork::new()
+        .read_from_csv_str(bad, 0.03, InputFormat::Plain)
+        .unwrap_err();
+    let parallel_err = TransmissionNetwork::new()
+        .read_from_csv_str_parallel(bad, 0.03, InputFormat::Plain)
+        .unwrap_err();
+    assert_eq!(parallel_err.to_string(), serial_err.to_string());
+}
+
+#[test]
+fn test_lenient_parsing_warnings() {
+    let csv = "A,B,0.01\n,C,0.01\nD,E,oops\nF,F,0.01\nG,H,0.01\n";
+
+    // By default the malformed row fails the read
+    assert!(TransmissionNetwork::new()
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .is_err());
+
+    let mut network = TransmissionNetwork::new();
+    network.lenient_parsing = true;
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+
+    let lines: Vec<u64> = network.warnings().iter().map(|w| w.line).collect();
+    assert_eq!(lines, vec![2, 3, 4]);
+    assert!(network.warnings()[1].message.contains("oops"));
+    assert_eq!(network.get_edge_count(), 2);
+    assert!(network.get_node("D").is_none());
+
+    // Unparseable dates are reported once per ID
+    let mut network = TransmissionNetwork::new();
+    network.lenient_parsing = true;
+    network
+        .read_from_csv_str(
+            "X|notadate,Y|2020-01-01,0.01\nX|notadate,Z|2020-02-01,0.01\n",
+            0.03,
+            InputFormat::AEH,
+        )
+        .unwrap();
+    assert_eq!(
+        network.warnings(),
+        &[ParseWarning {
+            line: 1,
+            message: "could not parse date 'notadate' in ID 'X|notadate'".to_string(),
+        }]
+    );
+
+    // The WASM-facing builder returns the network alongside the warnings
+    let result: serde_json::Value = serde_json::from_str(
+        &build_network_with_warnings_internal(csv, 0.03, InputFormat::Plain).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(result["warnings"].as_array().unwrap().len(), 3);
+    assert_eq!(result["warnings"][0]["line"], 2);
+    assert!(result["json"]["trace_results"]["Nodes"]["id"].is_array());
+}
+
+#[test]
+fn test_clusters_by_recency() {
+    let csv = "A_2019-03-01,B_2020-06-15,0.01\n\
+               C_2021-01-10,D_2018-05-05,0.01\n\
+               E,F,0.01\n";
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::PlainWithDate)
+        .unwrap();
+    network.compute_adjacency();
+    network.compute_clusters();
+
+    let cluster_of = |id: &str| network.get_node(id).unwrap().cluster_id.unwrap();
+    let ranked = network.clusters_by_recency();
+    assert_eq!(
+        ranked,
+        vec![
+            (
+                cluster_of("C_2021-01-10"),
+                Some(Utc.with_ymd_and_hms(2021, 1, 10, 0, 0, 0).unwrap())
+            ),
+            (
+                cluster_of("A_2019-03-01"),
+                Some(Utc.with_ymd_and_hms(2020, 6, 15, 0, 0, 0).unwrap())
+            ),
+            // Undated clusters sort last
+            (cluster_of("E"), None),
+        ]
+    );
+}
+
+#[test]
+fn test_links_added_between() {
+    let csv = "A|2019-01-10,B|2020-02-01,0.01\n\
+               B|2020-02-01,C|2020-03-15,0.01\n\
+               C|2020-03-15,D|2021-07-01,0.01\n\
+               D|2021-07-01,E,0.01\n";
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+        .unwrap();
+
+    let date = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+
+    // A-B formed 2020-02-01 and B-C 2020-03-15; C-D formed in 2021, and D-E
+    // has an undated endpoint
+    assert_eq!(
+        network.links_added_between(date(2020, 1, 1), date(2020, 12, 31)),
+        2
+    );
+    // The window bounds are inclusive
+    assert_eq!(
+        network.links_added_between(date(2020, 3, 15), date(2021, 7, 1)),
+        2
+    );
+    assert_eq!(
+        network.links_added_between(date(2022, 1, 1), date(2022, 12, 31)),
+        0
+    );
+}
+
+#[test]
+fn test_column_mapping() {
+    // Metadata columns interleaved with the edge fields, which are out of order
+    let csv = "run,dist,site,to,from,bootstrap\n\
+               r1,0.01,X,B,A,0.95\n\
+               r1,0.02,Y,C,B,0.3\n\
+               r2,0.5,Z,D,C,0.99\n";
+
+    let mut network = TransmissionNetwork::new();
+    network.strict_columns = true;
+    network.support_cutoff = Some(0.5);
+    network.column_mapping = Some(ColumnMapping {
+        source: "from".to_string(),
+        target: "to".to_string(),
+        distance: "dist".to_string(),
+        support: Some("bootstrap".to_string()),
+    });
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap();
+
+    let mut edges: Vec<(String, String, f64, Option<f64>)> = network
+        .edges
+        .iter()
+        .map(|e| {
+            (
+                e.source_id.clone(),
+                e.target_id.clone(),
+                e.distance,
+                e.support,
+            )
+        })
+        .collect();
+    edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(
+        edges,
+        vec![
+            ("A".to_string(), "B".to_string(), 0.01, Some(0.95)),
+            ("B".to_string(), "C".to_string(), 0.02, Some(0.3)),
+        ]
+    );
+    assert!(network.edges.iter().any(|e| e.is_unsupported));
+    assert!(network.get_node("D").is_some());
+
+    // A mapped column missing from the header is an error
+    let mut network = TransmissionNetwork::new();
+    network.column_mapping = Some(ColumnMapping {
+        source: "from".to_string(),
+        target: "to".to_string(),
+        distance: "distance".to_string(),
+        support: None,
+    });
+    let err = network
+        .read_from_csv_str(csv, 0.03, InputFormat::Plain)
+        .unwrap_err();
+    assert!(err.to_string().contains("'distance'"));
+}
+
+#[test]
+fn test_node_date_range() {
+    let csv = "A|2019-04-01,B|2020-01-01,0.01\n\
+               A|2021-06-30,C,0.01\n\
+               A|2018-02-15,D,0.01\n";
+    let mut network = TransmissionNetwork::new();
+    network
+        .read_from_csv_str(csv, 0.03, InputFormat::AEH)
+        .unwrap();
+
+    let date = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+    assert_eq!(
+        network.node_date_range("A"),
+        Some((date(2018, 2, 15), date(2021, 6, 30)))
+    );
+    assert_eq!(
+        network.node_date_range("B"),
+        Some((date(2020, 1, 1), date(2020, 1, 1)))
+    );
+
+    // Undated and unknown nodes have no range
+    assert_eq!(network.node_date_range("C"), None);
+    assert_eq!(network.node_date_range("Z"), None);
+}
+
+#[test]
+fn test_input_format_from_str() {
+    assert_eq!("aeh".parse::<InputFormat>().unwrap(), InputFormat::AEH);
+    assert_eq!("LANL".parse::<InputFormat>().unwrap(), InputFormat::LANL);
+    assert_eq!("plain".parse::<InputFormat>().unwrap(), InputFormat::Plain);
+    assert_eq!(
+        "plain-date".parse::<InputFormat>().unwrap(),
+        InputFormat::PlainWithDate
+    );
+    assert_eq!("regex".parse::<InputFormat>().unwrap(), InputFormat::Regex);
+
+    // Unknown names are errors; callers decide whether to fall back
+    assert!("csv".parse::<InputFormat>().is_err());
+    assert!("".parse::<InputFormat>().is_err());
+}